@@ -39,13 +39,16 @@ use embassy_stm32::{gpio, spi};
 use embassy_time::{Delay, Duration, Instant, Timer};
 use embedded_graphics::{
     mono_font::{ascii::FONT_6X12, MonoTextStyle},
-    pixelcolor::{raw::ToBytes, Rgb565},
+    pixelcolor::Rgb565,
     prelude::*,
     primitives::{Circle, PrimitiveStyle, Rectangle, Triangle},
     text::Text,
 };
 use embedded_hal_bus::spi::ExclusiveDevice;
-use ssd1331_async::{BitDepth, Config, Framebuffer, Ssd1331, WritePixels};
+use ssd1331_async::{
+    text::{PackedFont, TextRenderer},
+    BitDepth, Config, Framebuffer, Ssd1331, WritePixels,
+};
 use static_cell::ConstStaticCell;
 
 use {defmt_rtt as _, panic_probe as _};
@@ -70,66 +73,6 @@ fn fast_config() -> embassy_stm32::Config {
     cfg
 }
 
-struct TextRenderer {
-    data: &'static [u8],
-    char_size: Size,
-    char_byte_count: usize,
-}
-
-impl TextRenderer {
-    pub fn new(data: &'static [u8], char_size: Size) -> Self {
-        let char_bit_count = char_size.width as usize * char_size.height as usize;
-        assert!(char_bit_count % 8 == 0);
-        Self {
-            data,
-            char_size,
-            char_byte_count: char_bit_count / 8,
-        }
-    }
-
-    fn unpack(&self, c: char, buf: &mut [u8], fc: &[u8], bc: &[u8]) {
-        assert!(fc.len() == bc.len());
-        let color_len = fc.len();
-        let idx = c as usize - ' ' as usize;
-        let start = idx * self.char_byte_count;
-        let mut i = 0;
-        for b in &self.data[start..start + self.char_byte_count] {
-            let mut code = *b;
-            for _ in 0..8 {
-                buf[i..i + color_len].copy_from_slice(if code & 1 == 1 { fc } else { bc });
-                code >>= 1;
-                i += color_len;
-            }
-        }
-    }
-
-    pub async fn render_text(
-        &self,
-        text: &str,
-        top_left: Point,
-        fc: Rgb565,
-        bc: Rgb565,
-        buf: &mut [u8],
-        display: &mut impl WritePixels,
-    ) {
-        let buf_size = self.char_size.width as usize * self.char_size.height as usize * 2;
-        let buf = &mut buf[..buf_size];
-        for (i, c) in text.chars().enumerate() {
-            self.unpack(c, buf, fc.to_be_bytes().as_ref(), bc.to_be_bytes().as_ref());
-            display
-                .write_pixels(
-                    buf,
-                    BitDepth::Sixteen,
-                    Rectangle::new(
-                        top_left + Point::new(i as i32 * self.char_size.width as i32, 0),
-                        self.char_size,
-                    ),
-                )
-                .await;
-        }
-    }
-}
-
 #[embassy_executor::main]
 async fn main(_spawner: Spawner) {
     let mut p = embassy_stm32::init(fast_config());
@@ -179,26 +122,16 @@ async fn main(_spawner: Spawner) {
     // character by character and transfer it to the screen. If we couldn't
     // spare 144 bytes, we could do this in even smaller chunks.
     let pixel_data = PIXEL_DATA.take();
-    let font = TextRenderer::new(include_bytes!("./font_6x12.bin"), Size::new(6, 12));
-    let start = Instant::now();
-    font.render_text(
-        "Hello",
-        Point::zero(),
-        Rgb565::CSS_FLORAL_WHITE,
-        Rgb565::CSS_INDIGO,
-        pixel_data,
-        &mut display,
-    )
-    .await;
-    font.render_text(
-        "Rust!",
-        Point::new(0, 12),
+    let font = TextRenderer::new(
+        PackedFont::new(include_bytes!("./font_6x12.bin"), Size::new(6, 12)),
         Rgb565::CSS_FLORAL_WHITE,
         Rgb565::CSS_INDIGO,
-        pixel_data,
-        &mut display,
-    )
-    .await;
+    );
+    let start = Instant::now();
+    font.draw(&mut display, "Hello", Point::zero(), pixel_data)
+        .await;
+    font.draw(&mut display, "Rust!", Point::new(0, 12), pixel_data)
+        .await;
     info!(
         "font render: {} us",
         Instant::now().duration_since(start).as_micros()
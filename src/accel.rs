@@ -0,0 +1,74 @@
+//! Drawing of `embedded-graphics` primitives with the controller's commands.
+
+use embedded_graphics::{
+    pixelcolor::Rgb565,
+    primitives::{Line, PrimitiveStyle, Rectangle, StrokeAlignment, Styled},
+};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{DataCommand, Error, StagedTarget};
+
+impl<RST, DI, PinE, SpiE> StagedTarget<'_, RST, DI, Rgb565>
+where
+    RST: OutputPin<Error = PinE>,
+    DI: DataCommand<Error = Error<PinE, SpiE>>,
+{
+    /// Draws the line with the controller's line command, if it's one pixel
+    /// wide and within the display, or pixel by pixel with [Self::draw]
+    /// otherwise.
+    ///
+    /// The staged pixels are committed first, to keep the drawing order.
+    pub async fn draw_line(
+        &mut self,
+        line: &Styled<Line, PrimitiveStyle<Rgb565>>,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        self.commit().await?;
+        let Styled { primitive, style } = line;
+        if let (Some(color), 1) = (style.stroke_color, style.stroke_width) {
+            match self
+                .display
+                .draw_line(primitive.start, primitive.end, color, delay)
+                .await
+            {
+                Err(Error::InvalidArea) => {}
+                result => return result,
+            }
+        }
+        self.draw(line).await
+    }
+
+    /// Draws the rectangle with the controller's rectangle command, if its
+    /// border is at most one pixel wide and it's within the display, or pixel
+    /// by pixel with [Self::draw] otherwise.
+    ///
+    /// The staged pixels are committed first, to keep the drawing order.
+    pub async fn draw_rectangle(
+        &mut self,
+        rectangle: &Styled<Rectangle, PrimitiveStyle<Rgb565>>,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        self.commit().await?;
+        let Styled { primitive, style } = rectangle;
+        let stroke = style.stroke_color.filter(|_| style.stroke_width > 0);
+        let (area, border, fill) = match (stroke, style.fill_color) {
+            (None, None) => return Ok(()),
+            (None, Some(fill)) => (*primitive, fill, Some(fill)),
+            (Some(stroke), fill) if style.stroke_width == 1 => {
+                // A one-pixel stroke is inside the rectangle unless aligned
+                // outside.
+                let area = match style.stroke_alignment {
+                    StrokeAlignment::Outside => primitive.offset(1),
+                    _ => *primitive,
+                };
+                (area, stroke, fill)
+            }
+            _ => return self.draw(rectangle).await,
+        };
+        match self.display.draw_rectangle(area, border, fill, delay).await {
+            Err(Error::InvalidArea) => self.draw(rectangle).await,
+            result => result,
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! Delta-frame animations.
+//!
+//! Each frame stores only the rectangles that changed since the previous
+//! frame, which keeps animated icons small enough for on-chip flash. The
+//! format is a sequence of frames with no header:
+//!
+//! ```text
+//! frame  := count:u8 region*count
+//! region := x:u8 y:u8 width:u8 height:u8 pixels
+//! ```
+//!
+//! Region coordinates are relative to the animation's top-left corner.
+//! Pixels are stored the same way as in [`Framebuffer`](crate::Framebuffer):
+//! `width * height` pixels of one byte for 8-bit depth, or two big-endian
+//! bytes for 16-bit depth. The first frame is typically a single region
+//! covering the whole animation.
+//!
+//! For frame pacing, see [`Animation`], or [`FrameTicker`] for animation
+//! loops drawn by the caller.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use embedded_graphics_core::{
+    geometry::{Point, Size},
+    primitives::Rectangle,
+};
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{BitDepth, WritePixels};
+
+/// Iterator over the frames of delta-encoded animation data.
+///
+/// Stops at the end of the data, or at the first truncated frame.
+#[derive(Clone)]
+pub struct DeltaFrames<'a> {
+    data: &'a [u8],
+    bit_depth: BitDepth,
+}
+
+impl<'a> DeltaFrames<'a> {
+    /// Creates an iterator over the frames in `data`.
+    pub const fn new(data: &'a [u8], bit_depth: BitDepth) -> Self {
+        Self { data, bit_depth }
+    }
+}
+
+impl<'a> Iterator for DeltaFrames<'a> {
+    type Item = DeltaFrame<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&count, rest) = self.data.split_first()?;
+        let mut regions = Regions {
+            data: rest,
+            remaining: count,
+            bit_depth: self.bit_depth,
+        };
+        for _ in 0..count {
+            if regions.next().is_none() {
+                self.data = &[];
+                return None;
+            }
+        }
+        let len = rest.len() - regions.data.len();
+        let frame = DeltaFrame {
+            data: &rest[..len],
+            count,
+            bit_depth: self.bit_depth,
+        };
+        self.data = regions.data;
+        Some(frame)
+    }
+}
+
+/// A single frame of a delta-encoded animation.
+#[derive(Clone)]
+pub struct DeltaFrame<'a> {
+    data: &'a [u8],
+    count: u8,
+    bit_depth: BitDepth,
+}
+
+impl<'a> DeltaFrame<'a> {
+    /// Returns the changed regions and their pixel data.
+    pub fn regions(&self) -> Regions<'a> {
+        Regions {
+            data: self.data,
+            remaining: self.count,
+            bit_depth: self.bit_depth,
+        }
+    }
+
+    /// Sends the changed regions to the display, with the animation's
+    /// top-left corner at `top_left`.
+    pub async fn apply(&self, display: &mut (impl WritePixels + ?Sized), top_left: Point) {
+        for (area, pixels) in self.regions() {
+            let area = Rectangle::new(top_left + area.top_left, area.size);
+            display.write_pixels(pixels, self.bit_depth, area).await;
+        }
+    }
+}
+
+/// Iterator over the changed regions of a [`DeltaFrame`].
+#[derive(Clone)]
+pub struct Regions<'a> {
+    data: &'a [u8],
+    remaining: u8,
+    bit_depth: BitDepth,
+}
+
+impl<'a> Iterator for Regions<'a> {
+    type Item = (Rectangle, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 || self.data.len() < 4 {
+            return None;
+        }
+        let [x, y, width, height] = [0, 1, 2, 3].map(|i| self.data[i]);
+        let len = width as usize * height as usize * self.bit_depth.bytes();
+        let pixels = self.data.get(4..4 + len)?;
+        self.data = &self.data[4 + len..];
+        self.remaining -= 1;
+        let area = Rectangle::new(
+            Point::new(x.into(), y.into()),
+            Size::new(width.into(), height.into()),
+        );
+        Some((area, pixels))
+    }
+}
+
+/// A frame of an [`Animation`].
+#[derive(Clone, Copy)]
+pub struct Frame<'a> {
+    /// Area covered by the frame, relative to the animation's top-left
+    /// corner. Can be a smaller region if the rest didn't change.
+    pub area: Rectangle,
+    /// Pixel data for the area, in the animation's bit depth.
+    pub data: &'a [u8],
+    /// How long the frame stays on screen, in milliseconds.
+    pub duration_ms: u32,
+}
+
+/// A sequence of frames played with fixed per-frame timing.
+///
+/// Playback can be paused, resumed or stopped through [`Animation::control`]
+/// from another task while [`Animation::play`] is running.
+pub struct Animation<'a> {
+    frames: &'a [Frame<'a>],
+    bit_depth: BitDepth,
+    looping: bool,
+    control: Control,
+}
+
+impl<'a> Animation<'a> {
+    /// Creates an animation that plays the frames once.
+    pub const fn new(frames: &'a [Frame<'a>], bit_depth: BitDepth) -> Self {
+        Self {
+            frames,
+            bit_depth,
+            looping: false,
+            control: Control::new(),
+        }
+    }
+
+    /// Sets whether playback restarts from the first frame after the last
+    /// one. Looping animations play until stopped.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Returns the playback control.
+    pub fn control(&self) -> &Control {
+        &self.control
+    }
+
+    /// Plays the animation with its top-left corner at `top_left`.
+    ///
+    /// Each frame is sent and then kept on screen for its duration, so the
+    /// transfer time adds to the frame time. Returns when the last frame
+    /// has been shown or playback is stopped; the control is reset to
+    /// playing before returning.
+    pub async fn play(
+        &self,
+        display: &mut (impl WritePixels + ?Sized),
+        delay: &mut impl DelayNs,
+        top_left: Point,
+    ) {
+        // A looping animation without frames would never wait.
+        if self.frames.is_empty() {
+            self.control.resume();
+            return;
+        }
+        'outer: loop {
+            for frame in self.frames {
+                loop {
+                    match self.control.state.load(Ordering::Relaxed) {
+                        STOPPED => break 'outer,
+                        PAUSED => delay.delay_ms(PAUSE_POLL_MS).await,
+                        _ => break,
+                    }
+                }
+                let area = Rectangle::new(top_left + frame.area.top_left, frame.area.size);
+                display.write_pixels(frame.data, self.bit_depth, area).await;
+                delay.delay_ms(frame.duration_ms).await;
+            }
+            if !self.looping {
+                break;
+            }
+        }
+        self.control.resume();
+    }
+}
+
+const PLAYING: u8 = 0;
+const PAUSED: u8 = 1;
+const STOPPED: u8 = 2;
+
+// How often a paused animation checks whether it was resumed.
+const PAUSE_POLL_MS: u32 = 10;
+
+/// Pause and stop requests for a playing [`Animation`].
+pub struct Control {
+    state: AtomicU8,
+}
+
+impl Control {
+    const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(PLAYING),
+        }
+    }
+
+    /// Pauses playback before the next frame.
+    pub fn pause(&self) {
+        // Don't override a pending stop.
+        let _ = self
+            .state
+            .compare_exchange(PLAYING, PAUSED, Ordering::Relaxed, Ordering::Relaxed);
+    }
+
+    /// Resumes paused playback.
+    pub fn resume(&self) {
+        self.state.store(PLAYING, Ordering::Relaxed);
+    }
+
+    /// Stops playback before the next frame, even if paused.
+    pub fn stop(&self) {
+        self.state.store(STOPPED, Ordering::Relaxed);
+    }
+
+    /// Returns whether playback is paused.
+    pub fn is_paused(&self) -> bool {
+        self.state.load(Ordering::Relaxed) == PAUSED
+    }
+}
+
+/// Paces a loop at a fixed frame rate, without drift.
+///
+/// Frame `n` is due `n / fps` seconds after the first call to
+/// [Self::wait_for_next_frame], measured by the `now_us` clock, so neither
+/// the time spent drawing nor the rounding of the period to microseconds
+/// adds up over frames. `now_us` returns a monotonic
+/// time in microseconds, e.g. `|| Instant::now().as_micros()` with Embassy.
+pub struct FrameTicker<D, F> {
+    delay: D,
+    now_us: F,
+    fps: u64,
+    // Start time of the schedule and number of the next frame, once started.
+    schedule: Option<(u64, u64)>,
+}
+
+impl<D, F> FrameTicker<D, F>
+where
+    D: DelayNs,
+    F: FnMut() -> u64,
+{
+    /// Panics if `fps` is zero, or over 1_000_000, which would make the
+    /// frame period shorter than the clock resolution.
+    pub fn new(delay: D, now_us: F, fps: u32) -> Self {
+        assert!(fps > 0 && 1_000_000 / fps > 0);
+        Self {
+            delay,
+            now_us,
+            fps: fps.into(),
+            schedule: None,
+        }
+    }
+
+    /// Waits until the next frame is due, and returns the number of frames
+    /// missed since the previous call.
+    ///
+    /// The first call returns after one frame period. If the loop ran late,
+    /// this returns without waiting; deadlines that passed entirely are
+    /// skipped and counted as missed, instead of running frames back to back
+    /// to catch up.
+    pub async fn wait_for_next_frame(&mut self) -> u32 {
+        let now = (self.now_us)();
+        let (start, frame) = *self.schedule.get_or_insert((now, 1));
+        let next = start + frame * 1_000_000 / self.fps;
+        if now < next {
+            self.delay
+                .delay_us((next - now).min(u32::MAX.into()) as u32)
+                .await;
+            self.schedule = Some((start, frame + 1));
+            return 0;
+        }
+        // The first frame due after now.
+        let upcoming = ((now - start + 1) * self.fps).div_ceil(1_000_000);
+        self.schedule = Some((start, upcoming));
+        (upcoming - frame - 1).min(u32::MAX.into()) as u32
+    }
+
+    /// Restarts the schedule from the next call, e.g. after the loop was
+    /// paused, so that the pause doesn't count as missed frames.
+    pub fn reset(&mut self) {
+        self.schedule = None;
+    }
+
+    /// Returns the delay.
+    pub fn release(self) -> D {
+        self.delay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{block_on, NoDelay, Recorder};
+
+    #[test]
+    fn empty_looping_animation_returns() {
+        let mut animation = Animation::new(&[], BitDepth::Sixteen);
+        animation.set_looping(true);
+        let mut display = Recorder::default();
+        block_on(animation.play(&mut display, &mut NoDelay, Point::zero()));
+        assert!(display.writes.is_empty());
+    }
+}
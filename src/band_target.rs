@@ -0,0 +1,84 @@
+use core::convert::Infallible;
+
+use embedded_graphics_core::{
+    pixelcolor::raw::ToBytes,
+    prelude::{Dimensions, DrawTarget, OriginDimensions, PixelColor, Point},
+    primitives::Rectangle,
+    Pixel,
+};
+
+use crate::{flush_bands, Framebuffer, TryWritePixels};
+
+/// A [`DrawTarget`] for one band of the area drawn by [`draw_in_bands`].
+///
+/// Accepts display coordinates, and only keeps the pixels inside the band.
+pub struct BandTarget<'a, 'b, C> {
+    fb: &'a mut Framebuffer<'b, C>,
+    offset: Point,
+}
+
+impl<'a, 'b, C> BandTarget<'a, 'b, C> {
+    // Creates a target where `offset` corresponds to the top-left corner of
+    // the framebuffer.
+    pub(crate) fn new(fb: &'a mut Framebuffer<'b, C>, offset: Point) -> Self {
+        Self { fb, offset }
+    }
+}
+
+impl<C> Dimensions for BandTarget<'_, '_, C> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(self.offset, self.fb.size())
+    }
+}
+
+impl<C> DrawTarget for BandTarget<'_, '_, C>
+where
+    C: PixelColor + ToBytes,
+    C::Bytes: AsRef<[u8]>,
+{
+    type Color = C;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let offset = self.offset;
+        self.fb
+            .draw_iter(pixels.into_iter().map(|Pixel(p, c)| Pixel(p - offset, c)))
+    }
+}
+
+/// Draws to an area of the display without a framebuffer of its size.
+///
+/// Bridges the synchronous `embedded-graphics` drawing model and the async
+/// transfers: the `draw` callback is called once per horizontal band of the
+/// area with a [`BandTarget`], and can draw anything to it in display
+/// coordinates, e.g. `Image::new(&bmp, Point::new(10, 10)).draw(target)`.
+/// Each band is sent as soon as it's drawn, using [`flush_bands`], so the
+/// two buffers need to hold only `band_height` rows of the area. Since
+/// everything is drawn once per band, taller bands trade RAM for speed.
+///
+/// Pixels that aren't drawn are left as they were in the buffers, so the
+/// callback typically clears the target first.
+///
+/// # Panics
+///
+/// If `band_height` is zero or the buffers are too small.
+pub async fn draw_in_bands<C, D>(
+    display: &mut D,
+    buffers: [&mut [u8]; 2],
+    area: Rectangle,
+    band_height: u32,
+    mut draw: impl FnMut(&mut BandTarget<'_, '_, C>),
+) -> Result<(), D::Error>
+where
+    D: TryWritePixels + ?Sized,
+    C: PixelColor + ToBytes,
+    C::Bytes: AsRef<[u8]>,
+{
+    flush_bands(display, buffers, area, band_height, |fb, band| {
+        draw(&mut BandTarget::new(fb, band.top_left))
+    })
+    .await
+}
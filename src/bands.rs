@@ -0,0 +1,141 @@
+use embedded_graphics_core::{
+    pixelcolor::raw::ToBytes,
+    prelude::{PixelColor, Point, Size},
+    primitives::Rectangle,
+};
+
+use crate::{futures::join, Framebuffer, TryWritePixels};
+
+/// Renders and sends a display area in horizontal bands, overlapping the
+/// rendering of each band with the transfer of the previous one.
+///
+/// The `render` callback is called for each band with a framebuffer of the
+/// band size and the band rectangle in display coordinates; the framebuffer
+/// origin corresponds to the top-left corner of the band. The bands are
+/// rendered alternately into the two buffers, each of which must hold
+/// `band_height` rows of the area.
+///
+/// The transfer of a band is started before rendering the next one, and
+/// runs concurrently with it. With a HAL that transfers the data in the
+/// background (e.g. using DMA), this overlaps the rendering with the
+/// transfer. Otherwise, it's equivalent to rendering and sending the bands
+/// one by one.
+///
+/// # Panics
+///
+/// If `band_height` is zero or the buffers are too small.
+pub async fn flush_bands<C, D>(
+    display: &mut D,
+    buffers: [&mut [u8]; 2],
+    area: Rectangle,
+    band_height: u32,
+    mut render: impl FnMut(&mut Framebuffer<'_, C>, Rectangle),
+) -> Result<(), D::Error>
+where
+    D: TryWritePixels + ?Sized,
+    C: PixelColor + ToBytes,
+{
+    assert!(band_height > 0);
+    if area.is_zero_sized() {
+        return Ok(());
+    }
+    let band = |y: u32| {
+        Rectangle::new(
+            area.top_left + Point::new(0, y as i32),
+            Size::new(area.size.width, band_height.min(area.size.height - y)),
+        )
+    };
+
+    let [mut current, mut next] = buffers;
+    let mut rect = band(0);
+    render(&mut Framebuffer::new(&mut *current, rect.size), rect);
+    loop {
+        let fb = Framebuffer::<C>::new(&mut *current, rect.size);
+        let write = display.try_write_pixels(fb.data(), fb.bit_depth(), rect);
+        let next_y = (rect.top_left.y - area.top_left.y) as u32 + rect.size.height;
+        if next_y == area.size.height {
+            return write.await;
+        }
+
+        // Send this band while the next one is rendered into the other
+        // buffer.
+        let next_rect = band(next_y);
+        let render_next = async {
+            render(&mut Framebuffer::new(&mut *next, next_rect.size), next_rect);
+        };
+        let (result, ()) = join(write, render_next).await;
+        result?;
+
+        core::mem::swap(&mut current, &mut next);
+        rect = next_rect;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::RefCell;
+    use core::convert::Infallible;
+    use std::{format, string::String, vec, vec::Vec};
+
+    use embedded_graphics_core::{pixelcolor::Rgb565, prelude::OriginDimensions};
+
+    use super::*;
+    use crate::{futures::yield_now, test_util::block_on, BitDepth};
+
+    // Logs the start and end of each transfer, which waits once in between
+    // like a DMA transfer would.
+    struct SlowDisplay<'a>(&'a RefCell<Vec<String>>);
+
+    impl OriginDimensions for SlowDisplay<'_> {
+        fn size(&self) -> Size {
+            Size::new(96, 64)
+        }
+    }
+
+    impl TryWritePixels for SlowDisplay<'_> {
+        type Error = Infallible;
+
+        async fn try_write_pixels(
+            &mut self,
+            _data: &[u8],
+            _bit_depth: BitDepth,
+            area: Rectangle,
+        ) -> Result<(), Infallible> {
+            self.0
+                .borrow_mut()
+                .push(format!("start {}", area.top_left.y));
+            yield_now().await;
+            self.0.borrow_mut().push(format!("end {}", area.top_left.y));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn renders_next_band_during_transfer() {
+        let log = RefCell::new(Vec::new());
+        let (mut a, mut b) = ([0; 2 * 2 * 2], [0; 2 * 2 * 2]);
+        let area = Rectangle::new(Point::new(0, 10), Size::new(2, 5));
+        let result = block_on(flush_bands::<Rgb565, _>(
+            &mut SlowDisplay(&log),
+            [&mut a, &mut b],
+            area,
+            2,
+            |_, band| log.borrow_mut().push(format!("render {}", band.top_left.y)),
+        ));
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            log.into_inner(),
+            vec![
+                "render 10",
+                "start 10",
+                "render 12",
+                "end 10",
+                "start 12",
+                "render 14",
+                "end 12",
+                "start 14",
+                "end 14",
+            ]
+        );
+    }
+}
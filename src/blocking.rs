@@ -0,0 +1,111 @@
+//! Use of the driver with blocking SPI and delays, without an executor.
+//!
+//! [`SpiInterface`] and [`Delay`] adapt the blocking `embedded-hal` traits
+//! to the ones the driver uses. The async methods then never wait for
+//! anything, and [`block_on`] runs them to completion in place:
+//!
+//! ```ignore
+//! let mut delay = Delay(delay);
+//! let interface = SpiInterface::new(dc, spi);
+//! let mut display = block_on(Ssd1331::with_interface(config, rst, interface, &mut delay))?;
+//! block_on(display.write_pixels(&buf, BitDepth::Sixteen, area))?;
+//! ```
+
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+use embedded_hal::{delay, digital::OutputPin, spi::SpiDevice};
+
+use crate::{DataCommand, Error};
+
+/// Runs a future of the driver to completion, polling it in a loop.
+///
+/// Meant for futures that only use blocking peripherals, which are ready on
+/// the first poll. A future waiting for something else, e.g. a shared
+/// framebuffer, spins until it's ready.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// Async delay that blocks on the wrapped one.
+pub struct Delay<D>(pub D);
+
+impl<D: delay::DelayNs> embedded_hal_async::delay::DelayNs for Delay<D> {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.0.delay_ns(ns)
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us)
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.0.delay_ms(ms)
+    }
+}
+
+/// Interface over a blocking SPI device and a D/C pin.
+///
+/// Same as the async [`SpiInterface`](crate::SpiInterface) otherwise.
+pub struct SpiInterface<DC, SPI> {
+    dc: DC,
+    spi: SPI,
+    // Last level set on the DC pin, if known: true for data, false for
+    // commands.
+    dc_data: Option<bool>,
+}
+
+impl<DC, SPI> SpiInterface<DC, SPI>
+where
+    DC: OutputPin,
+    SPI: SpiDevice,
+{
+    pub fn new(dc: DC, spi: SPI) -> Self {
+        Self {
+            dc,
+            spi,
+            dc_data: None,
+        }
+    }
+
+    /// Returns the pin and the SPI device.
+    pub fn release(self) -> (DC, SPI) {
+        (self.dc, self.spi)
+    }
+
+    fn write(&mut self, data: bool, bytes: &[u8]) -> Result<(), Error<DC::Error, SPI::Error>> {
+        if self.dc_data != Some(data) {
+            self.dc_data = None;
+            self.dc.set_state(data.into()).map_err(Error::Pin)?;
+            self.dc_data = Some(data);
+        }
+        self.spi.write(bytes).map_err(Error::Spi)
+    }
+}
+
+impl<DC, SPI> DataCommand for SpiInterface<DC, SPI>
+where
+    DC: OutputPin,
+    SPI: SpiDevice,
+{
+    type Error = Error<DC::Error, SPI::Error>;
+
+    async fn send_commands(&mut self, commands: &[u8]) -> Result<(), Self::Error> {
+        self.write(false, commands)
+    }
+
+    async fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.write(true, data)
+    }
+
+    fn reset(&mut self) {
+        self.dc_data = None;
+    }
+}
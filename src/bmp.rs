@@ -0,0 +1,44 @@
+//! BMP images using [tinybmp](https://crates.io/crates/tinybmp).
+
+use embedded_graphics_core::{
+    geometry::{OriginDimensions, Point},
+    pixelcolor::{raw::ToBytes, Rgb565},
+    primitives::Rectangle,
+};
+use tinybmp::Bmp;
+
+use crate::{expand::write_expanded, BitDepth, TryAdapter, WritePixels};
+
+/// Sends a BMP image to the display, with its top-left corner at
+/// `top_left`.
+///
+/// The pixels are converted into `buf` as many whole rows at a time as it
+/// can hold, and each batch of rows is sent with a single transfer, so a
+/// buffer of one row is enough. Any BMP format supported by tinybmp works,
+/// including indexed and RLE-compressed images.
+///
+/// # Panics
+///
+/// If `buf` can't hold a row of the image in 16-bit color.
+pub async fn flush_bmp(
+    display: &mut (impl WritePixels + ?Sized),
+    bmp: &Bmp<'_, Rgb565>,
+    top_left: Point,
+    buf: &mut [u8],
+) {
+    // The pixels come in row order, starting at the top-left corner.
+    let mut pixels = bmp.pixels();
+    let area = Rectangle::new(top_left, bmp.size());
+    let Ok(()) = write_expanded(
+        &mut TryAdapter(display),
+        area,
+        BitDepth::Sixteen,
+        buf,
+        |_, row| {
+            for (dst, pixel) in row.chunks_mut(2).zip(pixels.by_ref()) {
+                dst.copy_from_slice(&pixel.1.to_be_bytes());
+            }
+        },
+    )
+    .await;
+}
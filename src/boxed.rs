@@ -0,0 +1,93 @@
+use alloc::boxed::Box;
+use core::{future::Future, pin::Pin};
+
+use embedded_graphics_core::{
+    prelude::{OriginDimensions, Size},
+    primitives::Rectangle,
+};
+
+use crate::{BitDepth, WritePixels};
+
+// Object-safe version of WritePixels, returning boxed futures.
+trait ErasedWritePixels {
+    fn size(&self) -> Size;
+
+    fn write_pixels<'a>(
+        &'a mut self,
+        data: &'a [u8],
+        bit_depth: BitDepth,
+        area: Rectangle,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'a>>;
+}
+
+impl<T: WritePixels> ErasedWritePixels for T {
+    fn size(&self) -> Size {
+        OriginDimensions::size(self)
+    }
+
+    fn write_pixels<'a>(
+        &'a mut self,
+        data: &'a [u8],
+        bit_depth: BitDepth,
+        area: Rectangle,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'a>> {
+        Box::pin(WritePixels::write_pixels(self, data, bit_depth, area))
+    }
+}
+
+// A display borrowed by a BoxedDisplay.
+struct Borrowed<'a, T: ?Sized>(&'a mut T);
+
+impl<T: WritePixels + ?Sized> ErasedWritePixels for Borrowed<'_, T> {
+    fn size(&self) -> Size {
+        self.0.size()
+    }
+
+    fn write_pixels<'a>(
+        &'a mut self,
+        data: &'a [u8],
+        bit_depth: BitDepth,
+        area: Rectangle,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'a>> {
+        Box::pin(self.0.write_pixels(data, bit_depth, area))
+    }
+}
+
+/// A display of any type, behind a box.
+///
+/// [`WritePixels`] can't be used as a trait object, as its methods are
+/// async; this wrapper erases the display type, so libraries can hold
+/// "some display" without its type parameters. Each transfer allocates its
+/// future on the heap. All the methods of [`WritePixels`] are available
+/// through the wrapper.
+pub struct BoxedDisplay<'a> {
+    inner: Box<dyn ErasedWritePixels + 'a>,
+}
+
+impl<'a> BoxedDisplay<'a> {
+    /// Takes ownership of the display, e.g. a driver instance.
+    pub fn new(display: impl WritePixels + 'a) -> Self {
+        Self {
+            inner: Box::new(display),
+        }
+    }
+
+    /// Borrows the display for the lifetime of the wrapper.
+    pub fn from_mut(display: &'a mut (impl WritePixels + ?Sized)) -> Self {
+        Self {
+            inner: Box::new(Borrowed(display)),
+        }
+    }
+}
+
+impl OriginDimensions for BoxedDisplay<'_> {
+    fn size(&self) -> Size {
+        self.inner.size()
+    }
+}
+
+impl WritePixels for BoxedDisplay<'_> {
+    async fn write_pixels(&mut self, data: &[u8], bit_depth: BitDepth, area: Rectangle) {
+        self.inner.write_pixels(data, bit_depth, area).await
+    }
+}
@@ -0,0 +1,112 @@
+use embedded_graphics_core::{
+    pixelcolor::raw::ToBytes,
+    prelude::{PixelColor, Point, Size},
+    primitives::Rectangle,
+};
+
+use crate::{BitDepth, WritePixels};
+
+/// Bar chart of the latest samples that updates one column at a time.
+///
+/// Each sample is a column of the chart area, filled from the bottom in
+/// proportion to the value. Like on an oscilloscope, new samples sweep from
+/// left to right over the oldest ones, so adding a sample sends just one
+/// column of pixels instead of the whole plot. The levels of the samples
+/// are kept in a caller-provided buffer with a byte per column, which allows
+/// to redraw the chart.
+pub struct StripChart<'a, C> {
+    area: Rectangle,
+    min: i32,
+    max: i32,
+    levels: &'a mut [u8],
+    // Column for the next sample.
+    next: usize,
+    fg: C,
+    bg: C,
+}
+
+impl<'a, C> StripChart<'a, C>
+where
+    C: PixelColor + ToBytes,
+    C::Bytes: AsRef<[u8]>,
+{
+    /// Creates an empty chart in the area of the display, for values in the
+    /// `min..=max` range.
+    ///
+    /// The `levels` buffer needs a byte for each column of the area. Nothing
+    /// is drawn until the first sample or [Self::redraw].
+    ///
+    /// Panics if `levels` is too small, if the area is taller than 255
+    /// pixels, or if `min` is not less than `max`.
+    pub fn new(area: Rectangle, min: i32, max: i32, levels: &'a mut [u8], fg: C, bg: C) -> Self {
+        assert!(min < max && area.size.height <= u8::MAX as u32);
+        let levels = &mut levels[..area.size.width as usize];
+        levels.fill(0);
+        Self {
+            area,
+            min,
+            max,
+            levels,
+            next: 0,
+            fg,
+            bg,
+        }
+    }
+
+    /// Adds a sample and draws its column.
+    ///
+    /// Values outside the range are clamped. The `buf` needs to hold a
+    /// column of the area in the chart's color depth.
+    ///
+    /// Panics if `buf` is too small.
+    pub async fn push(
+        &mut self,
+        display: &mut (impl WritePixels + ?Sized),
+        value: i32,
+        buf: &mut [u8],
+    ) {
+        if self.levels.is_empty() {
+            return;
+        }
+        let value = value.clamp(self.min, self.max);
+        let height = i64::from(self.area.size.height);
+        let range = i64::from(self.max) - i64::from(self.min);
+        let level = (i64::from(value) - i64::from(self.min)) * height / range;
+        self.levels[self.next] = level as u8;
+        self.draw_column(display, self.next, buf).await;
+        self.next = (self.next + 1) % self.levels.len();
+    }
+
+    /// Draws all the columns of the chart again.
+    ///
+    /// See [Self::push] for `buf`.
+    pub async fn redraw(&self, display: &mut (impl WritePixels + ?Sized), buf: &mut [u8]) {
+        for column in 0..self.levels.len() {
+            self.draw_column(display, column, buf).await;
+        }
+    }
+
+    async fn draw_column(
+        &self,
+        display: &mut (impl WritePixels + ?Sized),
+        column: usize,
+        buf: &mut [u8],
+    ) {
+        let fg = self.fg.to_be_bytes();
+        let bg = self.bg.to_be_bytes();
+        let bpp = fg.as_ref().len();
+        let height = self.area.size.height as usize;
+        let empty = height - self.levels[column] as usize;
+        let pixels = buf[..height * bpp].chunks_mut(bpp);
+        for (y, pixel) in pixels.enumerate() {
+            pixel.copy_from_slice(if y < empty { bg.as_ref() } else { fg.as_ref() });
+        }
+        let area = Rectangle::new(
+            self.area.top_left + Point::new(column as i32, 0),
+            Size::new(1, height as u32),
+        );
+        display
+            .write_pixels(&buf[..height * bpp], BitDepth::of::<C>(), area)
+            .await;
+    }
+}
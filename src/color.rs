@@ -0,0 +1,141 @@
+//! Color math with integer operations.
+//!
+//! The blending functions use the classic trick of averaging all three
+//! channels of packed RGB565 values at once, so blending costs a few integer
+//! operations per pixel. Only fixed ratios are supported; see [`Opacity`].
+//! For arbitrary ratios, and for [`Rgb332`], see [`ColorMath`].
+
+use embedded_graphics_core::pixelcolor::{raw::RawU16, Rgb565, Rgb888};
+use embedded_graphics_core::prelude::{RawData, RgbColor};
+
+use crate::Rgb332;
+
+/// Opacity of the color blended over another one.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Opacity {
+    Quarter,
+    Half,
+    ThreeQuarters,
+}
+
+/// Returns the average of two colors.
+pub fn blend_half(a: Rgb565, b: Rgb565) -> Rgb565 {
+    from_raw(half(to_raw(a), to_raw(b)))
+}
+
+/// Returns `b` blended over `a` with 25% opacity.
+pub fn blend_quarter(a: Rgb565, b: Rgb565) -> Rgb565 {
+    blend(a, b, Opacity::Quarter)
+}
+
+/// Returns `b` blended over `a` with the given opacity.
+pub fn blend(a: Rgb565, b: Rgb565, opacity: Opacity) -> Rgb565 {
+    from_raw(blend_raw(to_raw(a), to_raw(b), opacity))
+}
+
+// Blends packed RGB565 values.
+pub(crate) fn blend_raw(a: u16, b: u16, opacity: Opacity) -> u16 {
+    match opacity {
+        Opacity::Quarter => half(a, half(a, b)),
+        Opacity::Half => half(a, b),
+        Opacity::ThreeQuarters => half(b, half(a, b)),
+    }
+}
+
+// Averages each channel, rounding down. Clearing the lowest bit of each
+// channel before the shift keeps it from spilling into the next channel.
+fn half(a: u16, b: u16) -> u16 {
+    (a & b) + (((a ^ b) & 0xF7DE) >> 1)
+}
+
+fn to_raw(c: Rgb565) -> u16 {
+    RawU16::from(c).into_inner()
+}
+
+fn from_raw(raw: u16) -> Rgb565 {
+    RawU16::new(raw).into()
+}
+
+/// Interpolation and brightness scaling for the display's color formats.
+///
+/// Amounts are given out of 255, and each channel is rounded to the nearest
+/// value, e.g. for fades, highlights and gradients.
+pub trait ColorMath: RgbColor {
+    /// Returns the color `t`/255 of the way from `self` to `other`.
+    fn lerp(self, other: Self, t: u8) -> Self;
+
+    /// Scales the brightness of each channel by `factor`/255.
+    fn scale(self, factor: u8) -> Self {
+        self.lerp(Self::BLACK, 255 - factor)
+    }
+
+    /// Moves the color `amount`/255 of the way towards white.
+    fn lighten(self, amount: u8) -> Self {
+        self.lerp(Self::WHITE, amount)
+    }
+
+    /// Moves the color `amount`/255 of the way towards black.
+    fn darken(self, amount: u8) -> Self {
+        self.lerp(Self::BLACK, amount)
+    }
+}
+
+impl ColorMath for Rgb565 {
+    fn lerp(self, other: Self, t: u8) -> Self {
+        Self::new(
+            lerp_channel(self.r(), other.r(), t),
+            lerp_channel(self.g(), other.g(), t),
+            lerp_channel(self.b(), other.b(), t),
+        )
+    }
+}
+
+impl ColorMath for Rgb332 {
+    fn lerp(self, other: Self, t: u8) -> Self {
+        Self::new(
+            lerp_channel(self.r(), other.r(), t),
+            lerp_channel(self.g(), other.g(), t),
+            lerp_channel(self.b(), other.b(), t),
+        )
+    }
+}
+
+fn lerp_channel(a: u8, b: u8, t: u8) -> u8 {
+    let (a, b, t) = (i32::from(a), i32::from(b), i32::from(t));
+    let d = (b - a) * t;
+    // Rounds half away from zero, so that the result is symmetric.
+    (a + (d + d.signum() * 127) / 255) as u8
+}
+
+/// Converts a hue, saturation and value to a color.
+///
+/// All components are out of 255, and the hue wraps around: 0 is red, 85
+/// green and 170 blue. That makes hue rotation a `wrapping_add`, e.g. for
+/// rainbow or breathing effects.
+pub fn hsv_to_rgb565(h: u8, s: u8, v: u8) -> Rgb565 {
+    hsv_to_rgb888(h, s, v).into()
+}
+
+/// Same as [hsv_to_rgb565], for 8-bit color mode.
+pub fn hsv_to_rgb332(h: u8, s: u8, v: u8) -> Rgb332 {
+    hsv_to_rgb888(h, s, v).into()
+}
+
+fn hsv_to_rgb888(h: u8, s: u8, v: u8) -> Rgb888 {
+    let (h, s, v) = (u32::from(h), u32::from(s), u32::from(v));
+    // Position within one of the six sectors of the hue circle, out of 255.
+    let sector = h * 6 / 256;
+    let f = h * 6 % 256;
+    let p = (v * (255 - s) / 255) as u8;
+    let q = (v * (255 - s * f / 255) / 255) as u8;
+    let t = (v * (255 - s * (255 - f) / 255) / 255) as u8;
+    let v = v as u8;
+    match sector {
+        0 => Rgb888::new(v, t, p),
+        1 => Rgb888::new(q, v, p),
+        2 => Rgb888::new(p, v, t),
+        3 => Rgb888::new(p, q, v),
+        4 => Rgb888::new(t, p, v),
+        _ => Rgb888::new(v, p, q),
+    }
+}
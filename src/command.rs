@@ -38,8 +38,13 @@ pub(crate) enum Command {
     /// Draw rectangle with given border and (if fill mode is enabled)
     /// interior colors. Requires that the rectangle is not empty.
     DrawRectangle(Rectangle, Rgb565, Rgb565),
+    /// Copy the window of RAM to the given top-left corner. Both are in RAM
+    /// coordinates. Similar caveats to ClearWindow.
+    Copy(Rectangle, Point),
     /// Set fill enabled or disabled for DrawRectangle command.
     SetFillEnabled(bool),
+    /// Set the number of COM rows scanned, minus one. Default is 63.
+    MultiplexRatio(u8),
     /// No-op.
     NoOp,
 }
@@ -48,12 +53,19 @@ fn clamp(c: i32) -> u8 {
     (c.max(0) & 0xFF) as u8
 }
 
+// The drawing commands take 6-bit channels, with the 5-bit red and blue in
+// the upper bits.
+fn color_bytes(color: Rgb565) -> [u8; 3] {
+    [color.r() << 1, color.g(), color.b() << 1]
+}
+
 impl Command {
     pub fn push<const N: usize>(&self, buf: &mut Vec<u8, N>) -> bool {
         let result = match self {
             &Command::MasterCurrent(current) => &[0x87, current.min(15)],
             &Command::Contrast(r, g, b) => &[0x81, r, 0x82, g, 0x83, b] as &[u8],
             &Command::DisplayOn(on) => &[0xAE | (on as u8)],
+            &Command::MultiplexRatio(ratio) => &[0xA8, ratio.min(63)],
             &Command::RemapAndBitDepth(dm, cm) => &[
                 0xA0,
                 (dm.row_direction as u8)
@@ -83,30 +95,47 @@ impl Command {
                     clamp(br.y),
                 ]
             }
-            &Command::DrawLine(a, b, color) => &[
-                0x21,
-                clamp(a.x),
-                clamp(a.y),
-                clamp(b.x),
-                clamp(b.y),
-                color.r(),
-                color.g(),
-                color.b(),
-            ],
+            &Command::DrawLine(a, b, color) => {
+                let [red, green, blue] = color_bytes(color);
+                &[
+                    0x21,
+                    clamp(a.x),
+                    clamp(a.y),
+                    clamp(b.x),
+                    clamp(b.y),
+                    red,
+                    green,
+                    blue,
+                ]
+            }
             &Command::DrawRectangle(r, border, fill) => {
                 let br = r.bottom_right().unwrap();
+                let [border_r, border_g, border_b] = color_bytes(border);
+                let [fill_r, fill_g, fill_b] = color_bytes(fill);
                 &[
                     0x22,
                     clamp(r.top_left.x),
                     clamp(r.top_left.y),
                     clamp(br.x),
                     clamp(br.y),
-                    border.r(),
-                    border.g(),
-                    border.b(),
-                    fill.r(),
-                    fill.g(),
-                    fill.b(),
+                    border_r,
+                    border_g,
+                    border_b,
+                    fill_r,
+                    fill_g,
+                    fill_b,
+                ]
+            }
+            &Command::Copy(r, dst) => {
+                let br = r.bottom_right().unwrap();
+                &[
+                    0x23,
+                    clamp(r.top_left.x),
+                    clamp(r.top_left.y),
+                    clamp(br.x),
+                    clamp(br.y),
+                    clamp(dst.x),
+                    clamp(dst.y),
                 ]
             }
             &Command::SetFillEnabled(enabled) => &[0x26, enabled as u8],
@@ -0,0 +1,167 @@
+use embedded_graphics_core::{
+    pixelcolor::{raw::ToBytes, Rgb565},
+    prelude::Point,
+    primitives::Rectangle,
+};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+use heapless::Vec;
+
+use crate::{text::PackedFont, BitDepth, DataCommand, Error, Ssd1331};
+
+/// Error of a [`DisplayList`] that can't hold more operations. Nothing was
+/// recorded.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DisplayListFull;
+
+// A recorded drawing operation.
+#[derive(Clone, Copy)]
+enum Op<'a> {
+    Fill(Rectangle, Rgb565),
+    Rectangle(Rectangle, Rgb565, Option<Rgb565>),
+    Line(Point, Point, Rgb565),
+    Blit(&'a [u8], BitDepth, Rectangle),
+    Text(PackedFont<'a>, &'a str, Point, Rgb565, Rgb565),
+}
+
+/// Drawing operations recorded synchronously, to be sent to the display
+/// later with [Self::replay].
+///
+/// Bridges synchronous rendering code, e.g. in a callback or behind a
+/// mutex, and the async transfers. Fills, rectangles and lines are sent as
+/// the controller's drawing commands, and the pixel data of blits and text
+/// is borrowed rather than copied, so each operation takes a few dozen
+/// bytes regardless of its size. Holds up to `N` operations.
+pub struct DisplayList<'a, const N: usize> {
+    ops: Vec<Op<'a>, N>,
+}
+
+impl<'a, const N: usize> Default for DisplayList<'a, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, const N: usize> DisplayList<'a, N> {
+    pub const fn new() -> Self {
+        Self { ops: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Removes all the recorded operations.
+    pub fn clear(&mut self) {
+        self.ops.clear();
+    }
+
+    /// Records filling the area with the color, e.g. to clear it.
+    pub fn fill(&mut self, area: Rectangle, color: Rgb565) -> Result<(), DisplayListFull> {
+        self.push(Op::Fill(area, color))
+    }
+
+    /// Records a rectangle, see [Ssd1331::draw_rectangle].
+    pub fn rectangle(
+        &mut self,
+        area: Rectangle,
+        border: Rgb565,
+        fill: Option<Rgb565>,
+    ) -> Result<(), DisplayListFull> {
+        self.push(Op::Rectangle(area, border, fill))
+    }
+
+    /// Records a line, see [Ssd1331::draw_line].
+    pub fn line(&mut self, start: Point, end: Point, color: Rgb565) -> Result<(), DisplayListFull> {
+        self.push(Op::Line(start, end, color))
+    }
+
+    /// Records a transfer of pixel data, see [Ssd1331::write_pixels].
+    pub fn blit(
+        &mut self,
+        data: &'a [u8],
+        bit_depth: BitDepth,
+        area: Rectangle,
+    ) -> Result<(), DisplayListFull> {
+        self.push(Op::Blit(data, bit_depth, area))
+    }
+
+    /// Records a line of text with its top-left corner at `top_left`, drawn
+    /// one glyph per transfer like [`TextRenderer`](crate::text::TextRenderer)
+    /// does.
+    pub fn text(
+        &mut self,
+        font: PackedFont<'a>,
+        text: &'a str,
+        top_left: Point,
+        fg: Rgb565,
+        bg: Rgb565,
+    ) -> Result<(), DisplayListFull> {
+        self.push(Op::Text(font, text, top_left, fg, bg))
+    }
+
+    fn push(&mut self, op: Op<'a>) -> Result<(), DisplayListFull> {
+        self.ops.push(op).map_err(|_| DisplayListFull)
+    }
+
+    /// Sends the recorded operations to the display, in order.
+    ///
+    /// The list is kept, so it can be replayed again, e.g. after the
+    /// display was reset. The `buf` is used to unpack the glyphs of text,
+    /// and needs to hold one glyph in 16-bit color. The `delay` is used to
+    /// wait for the drawing commands to complete.
+    ///
+    /// Stops at the first error. An operation outside the display bounds
+    /// returns [Error::InvalidArea] like the corresponding driver method.
+    ///
+    /// # Panics
+    ///
+    /// If `buf` is too small for a glyph of recorded text.
+    pub async fn replay<RST, DI, PinE, SpiE>(
+        &self,
+        display: &mut Ssd1331<RST, DI>,
+        delay: &mut impl DelayNs,
+        buf: &mut [u8],
+    ) -> Result<(), Error<PinE, SpiE>>
+    where
+        RST: OutputPin<Error = PinE>,
+        DI: DataCommand<Error = Error<PinE, SpiE>>,
+    {
+        for op in &self.ops {
+            match *op {
+                Op::Fill(area, color) => {
+                    display
+                        .draw_rectangle(area, color, Some(color), delay)
+                        .await?
+                }
+                Op::Rectangle(area, border, fill) => {
+                    display.draw_rectangle(area, border, fill, delay).await?
+                }
+                Op::Line(start, end, color) => display.draw_line(start, end, color, delay).await?,
+                Op::Blit(data, bit_depth, area) => {
+                    display.write_pixels(data, bit_depth, area).await?
+                }
+                Op::Text(font, text, top_left, fg, bg) => {
+                    let size = font.char_size();
+                    let len = size.width as usize * size.height as usize * 2;
+                    for (i, c) in text.chars().enumerate() {
+                        font.unpack(c, buf, &fg.to_be_bytes(), &bg.to_be_bytes());
+                        let top_left = top_left + Point::new((i as u32 * size.width) as i32, 0);
+                        display
+                            .write_pixels(
+                                &buf[..len],
+                                BitDepth::Sixteen,
+                                Rectangle::new(top_left, size),
+                            )
+                            .await?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,345 @@
+//! Conversion of 16-bit colors to [`Rgb332`] with dithering.
+//!
+//! Truncating each channel to 3-3-2 bits turns smooth gradients into
+//! visible bands. Dithering adds a position-dependent offset before the
+//! truncation, so that neighboring pixels round in different directions and
+//! the average color stays close to the original.
+
+use embedded_graphics_core::{
+    pixelcolor::{
+        raw::{RawU16, RawU8},
+        Rgb565,
+    },
+    prelude::{OriginDimensions, Point, RawData, Size},
+    primitives::Rectangle,
+};
+
+use crate::{expand::write_expanded, BitDepth, Framebuffer, Rgb332, TryAdapter, WritePixels};
+
+// 4x4 Bayer matrix, with thresholds from 0 to 15.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Converts the color of the pixel at `p` with ordered (4x4 Bayer)
+/// dithering.
+///
+/// Uniform areas come out as a fixed pattern of the two nearest colors, so
+/// the result doesn't change from frame to frame for the same picture.
+pub fn dither_to_rgb332(color: Rgb565, p: Point) -> Rgb332 {
+    let raw = dither_raw(RawU16::from(color).into_inner(), p.x as usize, p.y as usize);
+    RawU8::new(raw).into()
+}
+
+// Dithers a packed RGB565 value into a packed RGB332 one.
+fn dither_raw(c: u16, x: usize, y: usize) -> u8 {
+    let t = u16::from(BAYER[y & 3][x & 3]);
+    // Adds the threshold scaled to the dropped bits, saturating at the
+    // channel maximum.
+    let channel = |value: u16, dropped: u32, max: u16| {
+        ((value + (t >> (4 - dropped))) >> dropped).min(max) as u8
+    };
+    let r = channel(c >> 11, 2, 7);
+    let g = channel((c >> 5) & 0x3f, 3, 7);
+    let b = channel(c & 0x1f, 3, 3);
+    (r << 5) | (g << 2) | b
+}
+
+impl Framebuffer<'_, Rgb332> {
+    /// Fills the framebuffer with the contents of a 16-bit one, using
+    /// ordered dithering (see [dither_to_rgb332]).
+    ///
+    /// Only the area the two framebuffers have in common is converted, and
+    /// marked dirty.
+    pub fn dither_from(&mut self, src: &Framebuffer<'_, Rgb565>) {
+        let area = Rectangle::new(Point::zero(), self.size())
+            .intersection(&Rectangle::new(Point::zero(), src.size()));
+        let Size { width, height } = area.size;
+        let (dst_width, src_width) = (self.size().width as usize, src.size().width as usize);
+        let src_data = src.data();
+        let dst_data = self.data_mut();
+        for y in 0..height as usize {
+            let src_row = &src_data[y * src_width * 2..][..width as usize * 2];
+            let dst_row = &mut dst_data[y * dst_width..][..width as usize];
+            for (x, (d, s)) in dst_row.iter_mut().zip(src_row.chunks(2)).enumerate() {
+                *d = dither_raw(u16::from_be_bytes([s[0], s[1]]), x, y);
+            }
+        }
+        self.mark_dirty(area);
+    }
+}
+
+/// Sends the dirty area of a 16-bit framebuffer to the display as 8-bit
+/// pixels, using ordered dithering (see [dither_to_rgb332]).
+///
+/// Halves the transfer size compared to sending the framebuffer as it is,
+/// for a display in 8-bit color mode. The dither pattern follows the
+/// framebuffer coordinates, so partial updates line up with the rest. The
+/// converted pixels are written into `buf` as many whole rows at a time as
+/// it can hold. The framebuffer's top-left corner is placed at `top_left` on
+/// the display, and the framebuffer is marked clean afterwards.
+///
+/// # Panics
+///
+/// If `buf` can't hold a row of the dirty area in 8-bit color.
+pub async fn flush_rgb565_as_rgb332(
+    display: &mut (impl WritePixels + ?Sized),
+    fb: &mut Framebuffer<'_, Rgb565>,
+    top_left: Point,
+    buf: &mut [u8],
+) {
+    let Some(dirty) = fb.take_dirty() else {
+        return;
+    };
+    let area = Rectangle::new(top_left + dirty.top_left, dirty.size);
+    let (x0, y0) = (dirty.top_left.x as usize, dirty.top_left.y as usize);
+    let width = fb.size().width as usize;
+    let data = fb.data();
+    let Ok(()) = write_expanded(
+        &mut TryAdapter(display),
+        area,
+        BitDepth::Eight,
+        buf,
+        |y, dst| {
+            let y = y0 + y as usize;
+            let src = &data[(y * width + x0) * 2..];
+            for (x, (d, s)) in dst.iter_mut().zip(src.chunks(2)).enumerate() {
+                *d = dither_raw(u16::from_be_bytes([s[0], s[1]]), x0 + x, y);
+            }
+        },
+    )
+    .await;
+}
+
+/// Sends the dirty area of a 16-bit framebuffer to the display as 8-bit
+/// pixels, using Floyd–Steinberg error diffusion.
+///
+/// Gives smoother results than [flush_rgb565_as_rgb332] for photos and
+/// gradients, at the cost of more work per pixel, and a pattern that can
+/// shift when the picture changes. The rounding errors of a row are carried
+/// to the next one in `errors`, which needs three values per pixel of a row
+/// of the dirty area. Each flush starts from zero errors at the top of the
+/// dirty area. See [flush_rgb565_as_rgb332] for the rest.
+///
+/// # Panics
+///
+/// If `errors` or `buf` can't hold a row of the dirty area.
+pub async fn flush_rgb565_as_rgb332_diffused(
+    display: &mut (impl WritePixels + ?Sized),
+    fb: &mut Framebuffer<'_, Rgb565>,
+    top_left: Point,
+    errors: &mut [i16],
+    buf: &mut [u8],
+) {
+    let Some(dirty) = fb.take_dirty() else {
+        return;
+    };
+    let area = Rectangle::new(top_left + dirty.top_left, dirty.size);
+    let (x0, y0) = (dirty.top_left.x as usize, dirty.top_left.y as usize);
+    let width = fb.size().width as usize;
+    let errors = &mut errors[..dirty.size.width as usize * 3];
+    errors.fill(0);
+    let data = fb.data();
+    let Ok(()) = write_expanded(
+        &mut TryAdapter(display),
+        area,
+        BitDepth::Eight,
+        buf,
+        |y, dst| {
+            let src = &data[((y0 + y as usize) * width + x0) * 2..];
+            diffuse_row(src, dst, errors);
+        },
+    )
+    .await;
+}
+
+// Channels of RGB565 as (shift, mask, RGB332 maximum).
+const CHANNELS: [(u32, u16, u16); 3] = [(11, 0x1f, 7), (5, 0x3f, 7), (0, 0x1f, 3)];
+
+// Converts a row of big-endian RGB565 pixels into RGB332 ones.
+//
+// The values are kept in sixteenths of the RGB565 channel steps, and the
+// diffused errors are scaled by 16 for their weights. On entry, `errors`
+// holds the errors diffused from the previous row, and on return the ones
+// for the next row. Errors are only passed to pixels within the row.
+fn diffuse_row(src: &[u8], dst: &mut [u8], errors: &mut [i16]) {
+    let width = dst.len();
+    // Per channel: the error for the next pixel of this row, the partial sum
+    // for the pixel below the previous one, and the previous pixel's error.
+    let mut right = [0i16; 3];
+    let mut below = [0i16; 3];
+    let mut previous = [0i16; 3];
+    for x in 0..width {
+        let c = u16::from_be_bytes([src[2 * x], src[2 * x + 1]]);
+        let mut out = 0;
+        for (i, &(shift, mask, max)) in CHANNELS.iter().enumerate() {
+            let (vmax, max) = (mask as i32 * 16, max as i32);
+            let value = ((c >> shift) & mask) as i32 * 16
+                + (i32::from(errors[x * 3 + i]) + i32::from(right[i])) / 16;
+            let value = value.clamp(0, vmax);
+            // Nearest RGB332 level, and its value on the RGB565 scale.
+            let q = (value * max * 2 + vmax) / (2 * vmax);
+            let err = (value - q * vmax / max) as i16;
+            out = (out << if max == 3 { 2 } else { 3 }) | q as u8;
+            right[i] = err * 7;
+            if x > 0 {
+                errors[(x - 1) * 3 + i] = below[i] + err * 3;
+            }
+            below[i] = previous[i] + err * 5;
+            previous[i] = err;
+        }
+        dst[x] = out;
+    }
+    if width > 0 {
+        errors[(width - 1) * 3..].copy_from_slice(&below);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics_core::{
+        draw_target::DrawTarget,
+        prelude::{PointsIter, RgbColor},
+    };
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::test_util::{block_on, Recorder};
+
+    #[test]
+    fn black_and_white_are_exact() {
+        for (x, y) in (0..4).flat_map(|y| (0..4).map(move |x| (x, y))) {
+            assert_eq!(dither_raw(0, x, y), 0);
+            assert_eq!(dither_raw(0xffff, x, y), 0xff);
+        }
+    }
+
+    #[test]
+    fn uniform_color_averages_to_original() {
+        // Sum of the channel levels over the Bayer tile, which is the
+        // original value scaled by 16 for the dropped bits, unless it
+        // saturates at the top level.
+        let tile_sum = |c: u16, shift: u32, mask: u8| -> u32 {
+            (0..16)
+                .map(|i| u32::from(dither_raw(c, i % 4, i / 4) >> shift & mask))
+                .sum()
+        };
+        for r in 0..=28 {
+            assert_eq!(tile_sum(r << 11, 5, 7), u32::from(r) * 4, "red {r}");
+        }
+        for g in 0..=56 {
+            assert_eq!(tile_sum(g << 5, 2, 7), u32::from(g) * 2, "green {g}");
+        }
+        for b in 0..=24 {
+            assert_eq!(tile_sum(b, 0, 3), u32::from(b) * 2, "blue {b}");
+        }
+    }
+
+    #[test]
+    fn pattern_follows_framebuffer_coordinates() {
+        let color = Rgb565::new(13, 27, 9);
+        let raw = RawU16::from(color).into_inner();
+        let mut data = [0; 8 * 8 * 2];
+        let mut fb = Framebuffer::<Rgb565>::new(&mut data, Size::new(8, 8));
+        fb.clear(color).unwrap();
+        fb.take_dirty();
+        let area = Rectangle::new(Point::new(2, 1), Size::new(3, 2));
+        fb.fill_solid(&area, color).unwrap();
+
+        let mut display = Recorder::default();
+        block_on(flush_rgb565_as_rgb332(
+            &mut display,
+            &mut fb,
+            Point::new(10, 20),
+            &mut [0; 8],
+        ));
+        assert_eq!(
+            display.areas(),
+            [Rectangle::new(Point::new(12, 21), Size::new(3, 2))]
+        );
+        let expected: Vec<u8> = area
+            .points()
+            .map(|p| dither_raw(raw, p.x as usize, p.y as usize))
+            .collect();
+        assert_eq!(display.bytes(), expected);
+        assert_eq!(fb.take_dirty(), None);
+
+        // The whole framebuffer converted gives the same pattern.
+        let mut dst_data = [0; 8 * 8];
+        let mut dst = Framebuffer::<Rgb332>::new(&mut dst_data, Size::new(8, 8));
+        dst.take_dirty();
+        dst.dither_from(&fb);
+        assert_eq!(
+            dst.take_dirty(),
+            Some(Rectangle::new(Point::zero(), Size::new(8, 8)))
+        );
+        for p in area.points() {
+            assert_eq!(
+                dst.data()[p.y as usize * 8 + p.x as usize],
+                RawU8::from(dither_to_rgb332(color, p)).into_inner()
+            );
+        }
+    }
+
+    fn rgb565_row(pixels: &[Rgb565]) -> Vec<u8> {
+        pixels
+            .iter()
+            .flat_map(|&c| RawU16::from(c).into_inner().to_be_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn diffuse_row_exact_colors_have_no_error() {
+        let src = rgb565_row(&[Rgb565::BLACK, Rgb565::WHITE, Rgb565::BLUE, Rgb565::GREEN]);
+        let (mut dst, mut errors) = ([0; 4], [0; 12]);
+        diffuse_row(&src, &mut dst, &mut errors);
+        assert_eq!(dst, [0x00, 0xff, 0x03, 0x1c]);
+        assert_eq!(errors, [0; 12]);
+    }
+
+    #[test]
+    fn diffuse_row_error_weights() {
+        // Red 13 of 31 rounds to level 3 of 7, 4 sixteenths of a step too
+        // high. The pixel on the right gets 7/16 of that, which rounds to
+        // nothing, and the row below 3/16, 5/16 and 1/16.
+        let src = rgb565_row(&[Rgb565::BLACK, Rgb565::new(13, 0, 0), Rgb565::BLACK]);
+        let (mut dst, mut errors) = ([0; 3], [0; 9]);
+        diffuse_row(&src, &mut dst, &mut errors);
+        assert_eq!(dst, [0, 3 << 5, 0]);
+        assert_eq!(errors, [-12, 0, 0, -20, 0, 0, -4, 0, 0]);
+
+        // The errors carried in are added to the pixels, here 8 steps of
+        // blue to 9 of 31, which then rounds to level 2 of 3 instead of 1.
+        let src = rgb565_row(&[Rgb565::new(0, 0, 9)]);
+        let mut dst = [0; 1];
+        diffuse_row(&src, &mut dst, &mut [0; 3]);
+        assert_eq!(dst, [1]);
+        diffuse_row(&src, &mut dst, &mut [0, 0, 8 * 16 * 16]);
+        assert_eq!(dst, [2]);
+    }
+
+    #[test]
+    fn diffused_area_averages_to_original() {
+        let color = Rgb565::new(13, 40, 9);
+        let src = rgb565_row(&[color; 16]);
+        let mut errors = [0; 16 * 3];
+        let mut sums = [0u32; 3];
+        for _ in 0..16 {
+            let mut dst = [0; 16];
+            diffuse_row(&src, &mut dst, &mut errors);
+            for c in dst {
+                sums[0] += u32::from(c >> 5);
+                sums[1] += u32::from(c >> 2 & 7);
+                sums[2] += u32::from(c & 3);
+            }
+        }
+        // Mean levels, scaled to the RGB565 channels.
+        let means = [
+            sums[0] as f32 / 256.0 * 31.0 / 7.0,
+            sums[1] as f32 / 256.0 * 63.0 / 7.0,
+            sums[2] as f32 / 256.0 * 31.0 / 3.0,
+        ];
+        let original = [13.0, 40.0, 9.0];
+        for (mean, original) in means.iter().zip(original) {
+            assert!((mean - original).abs() < 0.5, "{means:?}");
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use embedded_graphics_core::{
+    pixelcolor::raw::ToBytes,
+    prelude::{OriginDimensions, PixelColor, Point, Size},
+    primitives::Rectangle,
+};
+
+use crate::{futures::yield_now, Framebuffer, WritePixels};
+
+/// Two framebuffers of the same shape, one holding what's on the display and
+/// the other being drawn.
+///
+/// Draw the next frame into [Self::back], then call [Self::flush] to send it
+/// to the display and swap the buffers. Since the front buffer always matches
+/// the display contents, the flush can optionally send only the pixels that
+/// changed.
+///
+/// After a swap, the back buffer holds the frame before the last one. Either
+/// redraw it completely, or call [Self::copy_front] to start from the frame
+/// on the display.
+pub struct DoubleBuffer<'a, C> {
+    buffers: [Framebuffer<'a, C>; 2],
+    front: usize,
+    // Whether the front buffer has been sent to the display.
+    front_shown: bool,
+}
+
+impl<'a, C> DoubleBuffer<'a, C>
+where
+    C: PixelColor + ToBytes,
+{
+    /// Creates a double buffer with the given slices as storage.
+    ///
+    /// Panics if either slice is too small to hold the requested size.
+    pub fn new(a: &'a mut [u8], b: &'a mut [u8], size: Size) -> Self {
+        Self {
+            buffers: [Framebuffer::new(a, size), Framebuffer::new(b, size)],
+            front: 0,
+            front_shown: false,
+        }
+    }
+
+    /// The buffer to draw the next frame into.
+    pub fn back(&mut self) -> &mut Framebuffer<'a, C> {
+        &mut self.buffers[1 - self.front]
+    }
+
+    /// The buffer with the last flushed frame.
+    pub fn front(&self) -> &Framebuffer<'a, C> {
+        &self.buffers[self.front]
+    }
+
+    /// Swaps the front and back buffers without sending anything.
+    pub fn swap(&mut self) {
+        self.front = 1 - self.front;
+        self.front_shown = false;
+    }
+
+    /// Copies the front buffer contents into the back buffer.
+    pub fn copy_front(&mut self) {
+        let [a, b] = &mut self.buffers;
+        let (front, back) = if self.front == 0 { (a, b) } else { (b, a) };
+        back.data_mut().copy_from_slice(front.data());
+        back.mark_dirty(Rectangle::new(Point::zero(), back.size()));
+    }
+
+    /// Sends the back buffer to the display at `top_left` and swaps the
+    /// buffers.
+    ///
+    /// With `diff` set, only the pixels that differ from the front buffer are
+    /// sent (see [WritePixels::flush_diff]). The whole buffer is still sent
+    /// on the first flush, or after [Self::swap].
+    pub async fn flush(&mut self, display: &mut impl WritePixels, top_left: Point, diff: bool) {
+        let back = &self.buffers[1 - self.front];
+        if diff && self.front_shown {
+            display.flush_diff(back, self.front(), top_left).await;
+        } else {
+            display.flush(back, top_left).await;
+        }
+        self.front = 1 - self.front;
+        self.front_shown = true;
+    }
+}
+
+/// Two framebuffers shared between a rendering and a flushing task.
+///
+/// Call [Self::split] to get the handles for the two tasks. The renderer
+/// draws into the back buffer and presents it; the flusher then swaps the
+/// buffers and sends the new front buffer, while the renderer is already
+/// drawing the next frame. Only one frame can be pending: the renderer
+/// waits for the flusher to take it before getting the back buffer again.
+///
+/// Uses only atomic loads and stores, so it works on cores without
+/// compare-and-swap. Waiting is done by polling, yielding to the executor
+/// between the checks.
+pub struct SharedDoubleBuffer<'a, C> {
+    buffers: [UnsafeCell<Framebuffer<'a, C>>; 2],
+    front: AtomicUsize,
+    // Whether the back buffer holds a presented frame not yet swapped in.
+    // Set only by the renderer and cleared only by the flusher.
+    pending: AtomicBool,
+}
+
+// The handles from `split` make sure that each buffer is accessed by one
+// task at a time.
+unsafe impl<C: Send> Sync for SharedDoubleBuffer<'_, C> {}
+
+impl<'a, C> SharedDoubleBuffer<'a, C>
+where
+    C: PixelColor + ToBytes,
+{
+    /// Creates a double buffer with the given slices as storage.
+    ///
+    /// Panics if either slice is too small to hold the requested size.
+    pub fn new(a: &'a mut [u8], b: &'a mut [u8], size: Size) -> Self {
+        Self {
+            buffers: [
+                UnsafeCell::new(Framebuffer::new(a, size)),
+                UnsafeCell::new(Framebuffer::new(b, size)),
+            ],
+            front: AtomicUsize::new(0),
+            pending: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the handles for the rendering and the flushing task.
+    ///
+    /// For tasks that need `'static` handles, put the double buffer in a
+    /// `static`, e.g. with `StaticCell`.
+    pub fn split(&mut self) -> (RenderHandle<'_, 'a, C>, FlushHandle<'_, 'a, C>) {
+        (RenderHandle { shared: self }, FlushHandle { shared: self })
+    }
+}
+
+/// The rendering side of a [`SharedDoubleBuffer`].
+pub struct RenderHandle<'s, 'a, C> {
+    shared: &'s SharedDoubleBuffer<'a, C>,
+}
+
+impl<'a, C> RenderHandle<'_, 'a, C>
+where
+    C: PixelColor + ToBytes,
+{
+    /// Returns the back buffer to draw the next frame into, waiting until
+    /// the previously presented frame is taken by the flusher.
+    ///
+    /// The back buffer holds the frame before the last one, so it usually
+    /// needs to be redrawn completely.
+    pub async fn back(&mut self) -> &mut Framebuffer<'a, C> {
+        while self.shared.pending.load(Ordering::Acquire) {
+            yield_now().await;
+        }
+        let back = 1 - self.shared.front.load(Ordering::Acquire);
+        // The flusher only accesses the other buffer until the next frame is
+        // presented, and `present` can't be called while this borrow lasts.
+        unsafe { &mut *self.shared.buffers[back].get() }
+    }
+
+    /// Hands the frame drawn into the back buffer over to the flusher.
+    ///
+    /// Must be called only after drawing into the buffer from [Self::back].
+    pub fn present(&mut self) {
+        self.shared.pending.store(true, Ordering::Release);
+    }
+}
+
+/// The flushing side of a [`SharedDoubleBuffer`].
+pub struct FlushHandle<'s, 'a, C> {
+    shared: &'s SharedDoubleBuffer<'a, C>,
+}
+
+impl<'a, C> FlushHandle<'_, 'a, C>
+where
+    C: PixelColor + ToBytes,
+{
+    /// Waits for a presented frame, swaps the buffers and returns the new
+    /// front buffer to send to the display.
+    pub async fn next(&mut self) -> &Framebuffer<'a, C> {
+        while !self.shared.pending.load(Ordering::Acquire) {
+            yield_now().await;
+        }
+        self.swap()
+    }
+
+    /// Like [Self::next], but returns `None` instead of waiting if there's
+    /// no new frame.
+    pub fn try_next(&mut self) -> Option<&Framebuffer<'a, C>> {
+        self.shared
+            .pending
+            .load(Ordering::Acquire)
+            .then(|| self.swap())
+    }
+
+    fn swap(&mut self) -> &Framebuffer<'a, C> {
+        let front = 1 - self.shared.front.load(Ordering::Relaxed);
+        self.shared.front.store(front, Ordering::Release);
+        self.shared.pending.store(false, Ordering::Release);
+        // The renderer only accesses the other buffer until the flusher
+        // swaps again, and `swap` can't be called while this borrow lasts.
+        unsafe { &*self.shared.buffers[front].get() }
+    }
+}
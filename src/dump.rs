@@ -0,0 +1,108 @@
+use embedded_graphics_core::{pixelcolor::raw::ToBytes, prelude::OriginDimensions};
+
+use crate::{rgb332::TO_RGB565, BitDepth, Framebuffer};
+
+// Number of pixel data bytes per log message in Framebuffer::log_dump.
+#[cfg(feature = "defmt")]
+const LOG_CHUNK: usize = 64;
+
+// Sizes of the BMP file header and the V4 info header, which has the channel
+// masks for 16-bit color.
+const HEADER_LEN: usize = 14 + 108;
+
+impl<C: ToBytes> Framebuffer<'_, C> {
+    /// Returns the length of the BMP image written by [Self::encode_bmp].
+    pub fn bmp_len(&self) -> usize {
+        HEADER_LEN + self.bmp_row_len() * self.size().height as usize
+    }
+
+    /// Writes the contents as a 16-bit BMP image into `out`, e.g. to dump
+    /// them over a debug channel and compare with what's on the panel.
+    ///
+    /// Returns the length of the image, or `None` if `out` is too small (see
+    /// [Self::bmp_len]). 8-bit data is converted as Rgb332, the way the
+    /// display interprets it.
+    pub fn encode_bmp(&self, out: &mut [u8]) -> Option<usize> {
+        let len = self.bmp_len();
+        let out = out.get_mut(..len)?;
+        let size = self.size();
+        let (header, pixels) = out.split_at_mut(HEADER_LEN);
+        let fields: [&[u8]; 18] = [
+            b"BM",
+            &(len as u32).to_le_bytes(),
+            &0u32.to_le_bytes(),
+            &(HEADER_LEN as u32).to_le_bytes(),
+            &108u32.to_le_bytes(),
+            &size.width.to_le_bytes(),
+            // Negative height for rows stored from the top.
+            &(-(size.height as i32)).to_le_bytes(),
+            &[1, 0, 16, 0],
+            // Compression with channel masks.
+            &3u32.to_le_bytes(),
+            &((len - HEADER_LEN) as u32).to_le_bytes(),
+            // 72 DPI.
+            &[0x13, 0x0b, 0, 0, 0x13, 0x0b, 0, 0],
+            &[0; 8],
+            &0xf800u32.to_le_bytes(),
+            &0x07e0u32.to_le_bytes(),
+            &0x001fu32.to_le_bytes(),
+            &0u32.to_le_bytes(),
+            // sRGB color space.
+            b"BGRs",
+            // Unused endpoints and gamma.
+            &[0; 48],
+        ];
+        let mut pos = 0;
+        for field in fields {
+            header[pos..pos + field.len()].copy_from_slice(field);
+            pos += field.len();
+        }
+        let row_len = self.bmp_row_len();
+        for (dst, src) in pixels.chunks_mut(row_len).zip(self.rows()) {
+            let (dst, padding) = dst.split_at_mut(size.width as usize * 2);
+            padding.fill(0);
+            match self.bit_depth() {
+                BitDepth::Eight => {
+                    for (d, &s) in dst.chunks_mut(2).zip(src) {
+                        d.copy_from_slice(&TO_RGB565[s as usize].to_le_bytes());
+                    }
+                }
+                BitDepth::Sixteen => {
+                    for (d, s) in dst.chunks_mut(2).zip(src.chunks(2)) {
+                        d.copy_from_slice(&[s[1], s[0]]);
+                    }
+                }
+            }
+        }
+        Some(len)
+    }
+
+    // Length of a row of 16-bit pixels, padded to 4 bytes.
+    fn bmp_row_len(&self) -> usize {
+        (self.size().width as usize * 2).next_multiple_of(4)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<C: ToBytes> Framebuffer<'_, C> {
+    /// Logs the contents with `defmt`, so that a screenshot can be put
+    /// together from the log on the host.
+    ///
+    /// The first message has the size and the bit depth, followed by the
+    /// pixel data in hex, in chunks of up to 64 bytes prefixed with their
+    /// offset, and an end marker. All messages start with `fb:`.
+    pub fn log_dump(&self) {
+        let size = self.size();
+        let bits = self.bit_depth().bytes() as u8 * 8;
+        defmt::info!(
+            "fb: begin {=u32}x{=u32} {=u8}",
+            size.width,
+            size.height,
+            bits
+        );
+        for (i, chunk) in self.data().chunks(LOG_CHUNK).enumerate() {
+            defmt::info!("fb: {=usize} {=[u8]:x}", i * LOG_CHUNK, chunk);
+        }
+        defmt::info!("fb: end");
+    }
+}
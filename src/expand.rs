@@ -0,0 +1,44 @@
+use embedded_graphics_core::{
+    prelude::{Point, Size},
+    primitives::Rectangle,
+};
+
+use crate::{BitDepth, TryWritePixels};
+
+// Sends an area of the display with pixel data produced a row at a time.
+//
+// The `fill` callback is called with the row index within the area and a
+// buffer of the row size. As many rows as fit in `buf` are sent with a single
+// transfer. Panics if `buf` can't hold a row.
+pub(crate) async fn write_expanded<D: TryWritePixels + ?Sized>(
+    display: &mut D,
+    area: Rectangle,
+    bit_depth: BitDepth,
+    buf: &mut [u8],
+    mut fill: impl FnMut(u32, &mut [u8]),
+) -> Result<(), D::Error> {
+    let row_len = area.size.width as usize * bit_depth.bytes();
+    if row_len == 0 || area.size.height == 0 {
+        return Ok(());
+    }
+    let batch_rows = buf.len() / row_len;
+    assert!(batch_rows > 0);
+
+    let mut y = 0;
+    while y < area.size.height {
+        let rows = (batch_rows as u32).min(area.size.height - y);
+        let len = rows as usize * row_len;
+        for (i, row) in buf[..len].chunks_mut(row_len).enumerate() {
+            fill(y + i as u32, row);
+        }
+        let batch = Rectangle::new(
+            area.top_left + Point::new(0, y as i32),
+            Size::new(area.size.width, rows),
+        );
+        display
+            .try_write_pixels(&buf[..len], bit_depth, batch)
+            .await?;
+        y += rows;
+    }
+    Ok(())
+}
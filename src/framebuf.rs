@@ -0,0 +1,44 @@
+//! Framebuffers from
+//! [embedded-graphics-framebuf](https://crates.io/crates/embedded-graphics-framebuf).
+
+use embedded_graphics_core::{
+    geometry::Point,
+    pixelcolor::{raw::ToBytes, PixelColor},
+    primitives::Rectangle,
+};
+use embedded_graphics_framebuf::{backends::FrameBufferBackend, FrameBuf};
+
+use crate::{expand::write_expanded, BitDepth, TryAdapter, WritePixels};
+
+/// Sends the contents of a `FrameBuf` to the display, with its top-left
+/// corner at `top_left`.
+///
+/// Lets code that already draws into `embedded-graphics-framebuf` buffers
+/// use this driver without copying into a [`Framebuffer`](crate::Framebuffer)
+/// first. The pixels are converted into `buf` as many whole rows at a time
+/// as it can hold, and each batch of rows is sent with a single transfer.
+///
+/// # Panics
+///
+/// If `buf` can't hold a row of the framebuffer.
+pub async fn flush_framebuf<C, B>(
+    display: &mut (impl WritePixels + ?Sized),
+    fb: &FrameBuf<C, B>,
+    top_left: Point,
+    buf: &mut [u8],
+) where
+    C: PixelColor + ToBytes,
+    C::Bytes: AsRef<[u8]>,
+    B: FrameBufferBackend<Color = C>,
+{
+    let bit_depth = BitDepth::of::<C>();
+    let bpp = bit_depth.bytes();
+    let area = Rectangle::new(top_left, fb.size());
+    let Ok(()) = write_expanded(&mut TryAdapter(display), area, bit_depth, buf, |y, row| {
+        for (x, dst) in row.chunks_mut(bpp).enumerate() {
+            let color = fb.get_color_at(Point::new(x as i32, y as i32));
+            dst.copy_from_slice(color.to_be_bytes().as_ref());
+        }
+    })
+    .await;
+}
@@ -1,10 +1,12 @@
 use embedded_graphics_core::{
-    pixelcolor::raw::ToBytes,
-    prelude::{DrawTarget, OriginDimensions, PixelColor, Size},
+    image::GetPixel,
+    pixelcolor::raw::{RawData, ToBytes},
+    prelude::{Dimensions, DrawTarget, OriginDimensions, PixelColor, Point, Size},
+    primitives::Rectangle,
     Pixel,
 };
 
-use crate::BitDepth;
+use crate::{geometry::union, BitDepth, DISPLAY_HEIGHT, DISPLAY_WIDTH};
 
 /// Memory buffer that can serve as a [`DrawTarget`].
 ///
@@ -12,9 +14,20 @@ use crate::BitDepth;
 /// same slice of bytes to draw display areas of different shape or color
 /// depth, sequentially. Because of the dynamic shape, it's likely a bit
 /// slower.
+///
+/// The framebuffer keeps track of the bounding box of the pixels drawn since
+/// the last call to [Self::take_dirty], so that only that area can be sent
+/// to the display. A new framebuffer is considered entirely dirty. The rows
+/// touched are tracked as well, see [Self::take_dirty_rows].
+///
+/// With the `draw-stats` feature, the framebuffer also counts the pixels
+/// drawn and clipped, see [Self::draw_stats].
 pub struct Framebuffer<'a, C> {
     size: Size,
     data: &'a mut [u8],
+    dirty: Dirty,
+    #[cfg(feature = "draw-stats")]
+    stats: DrawStats,
     _color: core::marker::PhantomData<C>,
 }
 
@@ -32,27 +45,186 @@ where
         let s = Self {
             size,
             data,
+            dirty: Dirty::all(size),
+            #[cfg(feature = "draw-stats")]
+            stats: DrawStats::default(),
             _color: core::marker::PhantomData,
         };
         assert!(n >= s.pixel_count() * Self::BYTES_PER_PIXEL);
         s
     }
 
+    /// Creates a framebuffer with 16-bit aligned storage.
+    ///
+    /// Fills of 16-bit colors use 16-bit stores, and the storage can be
+    /// passed to a 16-bit DMA without copying. The pixels are still stored
+    /// as big-endian bytes, so on little-endian MCUs, the `u16` values have
+    /// their bytes swapped compared to the colors.
+    ///
+    /// Panics if the data slice is too small to hold the requested size.
+    pub fn from_u16(data: &'a mut [u16], size: Size) -> Self {
+        Self::new(bytemuck::cast_slice_mut(data), size)
+    }
+
+    /// Reuses the storage for a framebuffer of a different size.
+    ///
+    /// The contents are reinterpreted in the new shape, and the whole
+    /// framebuffer is marked dirty. Panics if the storage is too small.
+    pub fn reshape(self, size: Size) -> Self {
+        Self::new(self.data, size)
+    }
+
+    /// Reuses the storage for a framebuffer of the same size with a
+    /// different color type, e.g. to switch between 8 and 16-bit depth.
+    ///
+    /// The contents are reinterpreted in the new color type, and the whole
+    /// framebuffer is marked dirty. Panics if the storage is too small. Use
+    /// [Self::reshape] to change the size as well.
+    pub fn to_color<D: ToBytes>(self) -> Framebuffer<'a, D> {
+        Framebuffer::new(self.data, self.size)
+    }
+
     pub const fn bit_depth(&self) -> BitDepth {
-        match Self::BYTES_PER_PIXEL {
-            1 => BitDepth::Eight,
-            2 => BitDepth::Sixteen,
-            _ => panic!(),
-        }
+        BitDepth::of::<C>()
     }
 
     pub fn data(&self) -> &[u8] {
         &self.data[..self.pixel_count() * Self::BYTES_PER_PIXEL]
     }
 
+    // Returns the pixel data for modification. The caller is responsible for
+    // marking the modified area as dirty.
+    pub(crate) fn data_mut(&mut self) -> &mut [u8] {
+        let n = self.pixel_count() * Self::BYTES_PER_PIXEL;
+        &mut self.data[..n]
+    }
+
+    /// Returns an iterator over the rows of pixel data, from the top.
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        let row_len = self.row_len();
+        self.data().chunks(row_len)
+    }
+
+    /// Returns an iterator over the rows of pixel data for modification.
+    ///
+    /// Marks the whole framebuffer dirty.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [u8]> {
+        self.mark_dirty(Rectangle::new(Point::zero(), self.size));
+        let row_len = self.row_len();
+        self.data_mut().chunks_mut(row_len)
+    }
+
+    /// Computes a 32-bit FNV-1a hash of the pixel data in row `y`.
+    ///
+    /// Comparing the hashes with the ones from the previous frame allows to
+    /// find the changed rows without keeping a copy of the frame, at the cost
+    /// of a small chance of missing a change. See
+    /// [WritePixels::flush_changed_rows](crate::WritePixels::flush_changed_rows).
+    ///
+    /// Panics if the row is out of bounds.
+    pub fn row_hash(&self, y: u32) -> u32 {
+        assert!(y < self.size.height);
+        let start = y as usize * self.row_len();
+        self.data()[start..start + self.row_len()]
+            .iter()
+            .fold(0x811c_9dc5, |h, &b| {
+                (h ^ u32::from(b)).wrapping_mul(0x0100_0193)
+            })
+    }
+
+    // Length of a row in bytes; at least 1 to allow iterating over the rows
+    // of an empty framebuffer.
+    fn row_len(&self) -> usize {
+        (self.size.width as usize * Self::BYTES_PER_PIXEL).max(1)
+    }
+
     pub fn pixel_count(&self) -> usize {
         self.size.width as usize * self.size.height as usize
     }
+
+    /// Fills the whole framebuffer with the color.
+    ///
+    /// Faster than [`DrawTarget::clear`], as it writes the data a word at a
+    /// time.
+    pub fn clear_fast(&mut self, color: C)
+    where
+        C::Bytes: AsRef<[u8]>,
+    {
+        let bytes = color.to_be_bytes();
+        let bytes = bytes.as_ref();
+        let pattern = |offset: usize| {
+            let b = |i: usize| bytes[(offset + i) % bytes.len()];
+            u32::from_ne_bytes([b(0), b(1), b(2), b(3)])
+        };
+        let size = self.size;
+        let data = self.data_mut();
+        let (prefix, words, suffix) = bytemuck::pod_align_to_mut::<u8, u32>(data);
+        let words_offset = prefix.len();
+        let suffix_offset = words_offset + words.len() * 4;
+        for (i, b) in prefix.iter_mut().enumerate() {
+            *b = bytes[i % bytes.len()];
+        }
+        words.fill(pattern(words_offset));
+        for (i, b) in suffix.iter_mut().enumerate() {
+            *b = bytes[(suffix_offset + i) % bytes.len()];
+        }
+        self.mark_dirty(Rectangle::new(Point::zero(), size));
+    }
+
+    /// Returns the bounding box of the pixels drawn since the last call, if
+    /// any, and marks the framebuffer as clean.
+    pub fn take_dirty(&mut self) -> Option<Rectangle> {
+        self.dirty.take().area()
+    }
+
+    /// Returns a bitmap of the rows drawn to since the last call, and marks
+    /// the framebuffer as clean.
+    ///
+    /// Bit `n` is set if row `n` was drawn to. Rows below the 128th all map
+    /// to the last bit, which isn't a concern for framebuffers that fit on
+    /// the display. Unlike [Self::take_dirty], this allows to skip the
+    /// untouched rows between the changes; see
+    /// [WritePixels::flush_dirty_rows](crate::WritePixels::flush_dirty_rows).
+    pub fn take_dirty_rows(&mut self) -> u128 {
+        self.dirty.take().rows
+    }
+
+    // Extends the dirty rectangle to include the given area, which must be
+    // within the framebuffer bounds.
+    pub(crate) fn mark_dirty(&mut self, area: Rectangle) {
+        self.dirty.add(area);
+    }
+
+    /// Returns the pixel counters since the framebuffer was created, or
+    /// since the last [Self::reset_draw_stats].
+    #[cfg(feature = "draw-stats")]
+    pub fn draw_stats(&self) -> DrawStats {
+        self.stats
+    }
+
+    /// Resets the counters returned by [Self::draw_stats].
+    #[cfg(feature = "draw-stats")]
+    pub fn reset_draw_stats(&mut self) {
+        self.stats = DrawStats::default();
+    }
+
+    // Counts the pixels of an area of size `requested` that was clipped to
+    // `drawn`.
+    fn count_clipped(&mut self, requested: Size, drawn: Size) {
+        let (requested, drawn) = (pixels_in(requested), pixels_in(drawn));
+        self.count_pixels(drawn, requested - drawn);
+    }
+
+    // Adds to the pixel counters, if enabled.
+    fn count_pixels(&mut self, drawn: usize, clipped: usize) {
+        #[cfg(feature = "draw-stats")]
+        {
+            self.stats.drawn = self.stats.drawn.wrapping_add(drawn as u32);
+            self.stats.clipped = self.stats.clipped.wrapping_add(clipped as u32);
+        }
+        #[cfg(not(feature = "draw-stats"))]
+        let _ = (drawn, clipped);
+    }
 }
 
 impl<'a, C> OriginDimensions for Framebuffer<'a, C> {
@@ -73,17 +245,520 @@ where
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
-        for p in pixels {
-            let (Ok(x), Ok(y)) = (usize::try_from(p.0.x), usize::try_from(p.0.y)) else {
-                continue;
-            };
-            if x >= self.size.width as usize || y >= self.size.height as usize {
-                continue;
+        let width = self.size.width as usize;
+        let mut min = Point::new(i32::MAX, i32::MAX);
+        let mut max = Point::new(i32::MIN, i32::MIN);
+        // Primitives mostly produce horizontal runs of pixels, so the pixels
+        // that continue the current run skip the bounds checks and offset
+        // computation. Holds the first and the last point of the run.
+        let mut run: Option<(Point, Point)> = None;
+        let mut offset = 0;
+        let (mut drawn, mut clipped) = (0, 0);
+        for Pixel(p, color) in pixels {
+            match run {
+                Some((start, last)) if p == last + Point::new(1, 0) && p.x < width as i32 => {
+                    run = Some((start, p));
+                }
+                _ => {
+                    let (Ok(x), Ok(y)) = (usize::try_from(p.x), usize::try_from(p.y)) else {
+                        clipped += 1;
+                        continue;
+                    };
+                    if x >= width || y >= self.size.height as usize {
+                        clipped += 1;
+                        continue;
+                    }
+                    if let Some((start, last)) = run {
+                        min = min.component_min(start);
+                        max = max.component_max(last);
+                    }
+                    run = Some((p, p));
+                    offset = (y * width + x) * Self::BYTES_PER_PIXEL;
+                }
             }
-            let offset = (y * self.size.width as usize + x) * Self::BYTES_PER_PIXEL;
             self.data[offset..offset + Self::BYTES_PER_PIXEL]
-                .copy_from_slice(p.1.to_be_bytes().as_ref());
+                .copy_from_slice(color.to_be_bytes().as_ref());
+            offset += Self::BYTES_PER_PIXEL;
+            drawn += 1;
+        }
+        self.count_pixels(drawn, clipped);
+        if let Some((start, last)) = run {
+            min = min.component_min(start);
+            max = max.component_max(last);
+            self.mark_dirty(Rectangle::with_corners(min, max));
         }
         Ok(())
     }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let requested = area.size;
+        let area = area.intersection(&self.bounding_box());
+        self.count_clipped(requested, area.size);
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        let bytes = color.to_be_bytes();
+        let bytes = bytes.as_ref();
+        let width = self.size.width as usize;
+        let (x, y) = (area.top_left.x as usize, area.top_left.y as usize);
+        for row in y..y + area.size.height as usize {
+            let start = (row * width + x) * Self::BYTES_PER_PIXEL;
+            let end = start + area.size.width as usize * Self::BYTES_PER_PIXEL;
+            let row = &mut self.data[start..end];
+            if let [b] = bytes {
+                row.fill(*b);
+            } else if let (Ok(words), &[a, b]) = (bytemuck::try_cast_slice_mut(row), bytes) {
+                // Aligned 16-bit pixels, e.g. with storage from Self::from_u16.
+                words.fill(u16::from_ne_bytes([a, b]));
+            } else {
+                for pixel in row.chunks_mut(Self::BYTES_PER_PIXEL) {
+                    pixel.copy_from_slice(bytes);
+                }
+            }
+        }
+        self.mark_dirty(area);
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let clipped = area.intersection(&self.bounding_box());
+        self.count_clipped(area.size, clipped.size);
+        if clipped.is_zero_sized() {
+            return Ok(());
+        }
+        let mut colors = colors.into_iter();
+        // The colors cover the whole area, so skip the ones outside the
+        // framebuffer.
+        let area_width = area.size.width as usize;
+        let width = clipped.size.width as usize;
+        let left = (clipped.top_left.x - area.top_left.x) as usize;
+        skip(
+            &mut colors,
+            (clipped.top_left.y - area.top_left.y) as usize * area_width,
+        );
+        let (x, y) = (clipped.top_left.x as usize, clipped.top_left.y as usize);
+        for row in y..y + clipped.size.height as usize {
+            skip(&mut colors, left);
+            let start = (row * self.size.width as usize + x) * Self::BYTES_PER_PIXEL;
+            let end = start + width * Self::BYTES_PER_PIXEL;
+            let pixels = self.data[start..end].chunks_mut(Self::BYTES_PER_PIXEL);
+            for (pixel, color) in pixels.zip(colors.by_ref()) {
+                pixel.copy_from_slice(color.to_be_bytes().as_ref());
+            }
+            skip(&mut colors, area_width - left - width);
+        }
+        self.mark_dirty(clipped);
+        Ok(())
+    }
+}
+
+impl<C> Framebuffer<'_, C>
+where
+    C: PixelColor + ToBytes,
+    C::Bytes: AsRef<[u8]>,
+{
+    /// Fills the area with the color, clipped to the framebuffer.
+    ///
+    /// Same as [`DrawTarget::fill_solid`], without the `Result`. Filling the
+    /// rows directly is several times faster than drawing a styled
+    /// `embedded-graphics` rectangle, which goes through the pixel
+    /// iterators.
+    pub fn fill_rect(&mut self, area: Rectangle, color: C) {
+        let _ = self.fill_solid(&area, color);
+    }
+
+    /// Draws a horizontal line of `len` pixels to the right from `start`,
+    /// clipped to the framebuffer.
+    pub fn hline(&mut self, start: Point, len: u32, color: C) {
+        self.fill_rect(Rectangle::new(start, Size::new(len, 1)), color);
+    }
+
+    /// Draws a vertical line of `len` pixels down from `start`, clipped to
+    /// the framebuffer.
+    pub fn vline(&mut self, start: Point, len: u32, color: C) {
+        self.fill_rect(Rectangle::new(start, Size::new(1, len)), color);
+    }
+}
+
+impl<C> GetPixel for Framebuffer<'_, C>
+where
+    C: PixelColor + ToBytes + From<C::Raw>,
+{
+    type Color = C;
+
+    fn pixel(&self, p: Point) -> Option<C> {
+        read_pixel(self.data(), self.size, p)
+    }
+}
+
+// Reads the color of a pixel from big-endian data of the given size.
+pub(crate) fn read_pixel<C>(data: &[u8], size: Size, p: Point) -> Option<C>
+where
+    C: PixelColor + ToBytes + From<C::Raw>,
+{
+    let (Ok(x), Ok(y)) = (usize::try_from(p.x), usize::try_from(p.y)) else {
+        return None;
+    };
+    if x >= size.width as usize || y >= size.height as usize {
+        return None;
+    }
+    let bpp = core::mem::size_of::<C::Bytes>();
+    let offset = (y * size.width as usize + x) * bpp;
+    let raw = data[offset..offset + bpp]
+        .iter()
+        .fold(0, |acc, &b| acc << 8 | u32::from(b));
+    Some(C::Raw::from_u32(raw).into())
+}
+
+fn pixels_in(size: Size) -> usize {
+    size.width as usize * size.height as usize
+}
+
+/// Counters of the pixels drawn into a [`Framebuffer`], see
+/// [Framebuffer::draw_stats].
+///
+/// Only the drawing through [`DrawTarget`] is counted. The counters wrap
+/// around on overflow.
+#[cfg(feature = "draw-stats")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct DrawStats {
+    /// Number of pixels written to the framebuffer.
+    pub drawn: u32,
+    /// Number of pixels dropped for being out of bounds. A non-zero count
+    /// often means a widget is drawn in the wrong place.
+    pub clipped: u32,
+}
+
+// The area drawn since the last flush, along with the rows it touches.
+#[derive(Clone, Copy)]
+struct Dirty {
+    area: Rectangle,
+    rows: u128,
+}
+
+impl Dirty {
+    const CLEAN: Self = Self {
+        area: Rectangle::zero(),
+        rows: 0,
+    };
+
+    const fn all(size: Size) -> Self {
+        let area = Rectangle::new(Point::zero(), size);
+        Self {
+            area,
+            rows: row_mask(&area),
+        }
+    }
+
+    fn add(&mut self, area: Rectangle) {
+        self.rows |= row_mask(&area);
+        self.area = union(self.area, area);
+    }
+
+    fn take(&mut self) -> Self {
+        core::mem::replace(self, Self::CLEAN)
+    }
+
+    fn area(&self) -> Option<Rectangle> {
+        (!self.area.is_zero_sized()).then_some(self.area)
+    }
+}
+
+// Bits of the rows covered by the area, with the rows past the 128th mapped
+// to the last bit.
+const fn row_mask(area: &Rectangle) -> u128 {
+    if area.size.width == 0 || area.size.height == 0 {
+        return 0;
+    }
+    let first = area.top_left.y as u32;
+    let last = first + area.size.height - 1;
+    let (first, last) = (
+        if first < 127 { first } else { 127 },
+        if last < 127 { last } else { 127 },
+    );
+    (u128::MAX >> (127 - (last - first))) << first
+}
+
+fn skip(iter: &mut impl Iterator, n: usize) {
+    if n > 0 {
+        iter.nth(n - 1);
+    }
+}
+
+/// Framebuffer with statically sized storage of `W` x `H` pixels.
+///
+/// Owns its data, so it can be placed in a `static` (e.g. with
+/// `StaticCell`) without managing a separate byte slice. The size is checked
+/// at compile time to fit the display, either as is or rotated by 90
+/// degrees, e.g. 64x96 for [Config::ccw90](crate::Config::ccw90). Implements [`DrawTarget`]
+/// directly; for the functions that take a [`Framebuffer`], use
+/// [Self::as_framebuffer].
+pub struct FramebufferOwned<C: ToBytes, const W: usize, const H: usize> {
+    data: [[C::Bytes; W]; H],
+    dirty: Dirty,
+    #[cfg(feature = "draw-stats")]
+    stats: DrawStats,
+}
+
+impl<C, const W: usize, const H: usize, const B: usize> FramebufferOwned<C, W, H>
+where
+    C: ToBytes<Bytes = [u8; B]>,
+{
+    const SIZE_CHECK: () = assert!(
+        (W <= DISPLAY_WIDTH as usize && H <= DISPLAY_HEIGHT as usize)
+            || (W <= DISPLAY_HEIGHT as usize && H <= DISPLAY_WIDTH as usize),
+        "framebuffer is larger than the display"
+    );
+
+    const SIZE: Size = Size::new(W as u32, H as u32);
+
+    /// Creates a framebuffer filled with zeros (black).
+    pub const fn new() -> Self {
+        let () = Self::SIZE_CHECK;
+        Self {
+            data: [[[0; B]; W]; H],
+            dirty: Dirty::all(Self::SIZE),
+            #[cfg(feature = "draw-stats")]
+            stats: DrawStats {
+                drawn: 0,
+                clipped: 0,
+            },
+        }
+    }
+
+    pub const fn bit_depth(&self) -> BitDepth {
+        BitDepth::of::<C>()
+    }
+
+    pub fn data(&self) -> &[u8] {
+        self.data.as_flattened().as_flattened()
+    }
+
+    /// See [Framebuffer::take_dirty].
+    pub fn take_dirty(&mut self) -> Option<Rectangle> {
+        self.dirty.take().area()
+    }
+
+    /// See [Framebuffer::take_dirty_rows].
+    pub fn take_dirty_rows(&mut self) -> u128 {
+        self.dirty.take().rows
+    }
+
+    /// Returns a borrowed [`Framebuffer`] view of the data.
+    ///
+    /// The view starts with the dirty area of this framebuffer, and hands it
+    /// back when dropped, so drawing and flushing through the view keeps
+    /// track of the changes as usual.
+    pub fn as_framebuffer(&mut self) -> FramebufferMut<'_, C> {
+        let dirty = self.dirty.take();
+        FramebufferMut {
+            fb: Framebuffer {
+                size: Self::SIZE,
+                data: self.data.as_flattened_mut().as_flattened_mut(),
+                dirty,
+                #[cfg(feature = "draw-stats")]
+                stats: self.stats,
+                _color: core::marker::PhantomData,
+            },
+            dirty: &mut self.dirty,
+            #[cfg(feature = "draw-stats")]
+            stats: &mut self.stats,
+        }
+    }
+
+    /// See [Framebuffer::draw_stats].
+    #[cfg(feature = "draw-stats")]
+    pub fn draw_stats(&self) -> DrawStats {
+        self.stats
+    }
+
+    /// See [Framebuffer::reset_draw_stats].
+    #[cfg(feature = "draw-stats")]
+    pub fn reset_draw_stats(&mut self) {
+        self.stats = DrawStats::default();
+    }
+}
+
+impl<C, const W: usize, const H: usize, const B: usize> Default for FramebufferOwned<C, W, H>
+where
+    C: ToBytes<Bytes = [u8; B]>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: ToBytes, const W: usize, const H: usize> OriginDimensions for FramebufferOwned<C, W, H> {
+    fn size(&self) -> Size {
+        Size::new(W as u32, H as u32)
+    }
+}
+
+impl<C, const W: usize, const H: usize, const B: usize> DrawTarget for FramebufferOwned<C, W, H>
+where
+    C: PixelColor + ToBytes<Bytes = [u8; B]>,
+{
+    type Color = C;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.as_framebuffer().draw_iter(pixels)
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.as_framebuffer().fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.as_framebuffer().fill_solid(area, color)
+    }
+}
+
+impl<C, const W: usize, const H: usize, const B: usize> GetPixel for FramebufferOwned<C, W, H>
+where
+    C: PixelColor + ToBytes<Bytes = [u8; B]> + From<C::Raw>,
+{
+    type Color = C;
+
+    fn pixel(&self, p: Point) -> Option<C> {
+        read_pixel(self.data(), self.size(), p)
+    }
+}
+
+/// Borrowed [`Framebuffer`] view of a [`FramebufferOwned`].
+///
+/// Dereferences to the [`Framebuffer`]; the dirty area (and the pixel
+/// counters, if enabled) are handed back to the owner when the view is
+/// dropped.
+pub struct FramebufferMut<'a, C: ToBytes> {
+    fb: Framebuffer<'a, C>,
+    dirty: &'a mut Dirty,
+    #[cfg(feature = "draw-stats")]
+    stats: &'a mut DrawStats,
+}
+
+impl<'a, C: ToBytes> core::ops::Deref for FramebufferMut<'a, C> {
+    type Target = Framebuffer<'a, C>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.fb
+    }
+}
+
+impl<C: ToBytes> core::ops::DerefMut for FramebufferMut<'_, C> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.fb
+    }
+}
+
+impl<C: ToBytes> Drop for FramebufferMut<'_, C> {
+    fn drop(&mut self) {
+        *self.dirty = self.fb.dirty;
+        #[cfg(feature = "draw-stats")]
+        {
+            *self.stats = self.fb.stats;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics_core::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+
+    use super::*;
+    use crate::{
+        test_util::{block_on, Recorder},
+        Rgb332, WritePixels,
+    };
+
+    fn rect(x: i32, y: i32, w: u32, h: u32) -> Rectangle {
+        Rectangle::new(Point::new(x, y), Size::new(w, h))
+    }
+
+    #[test]
+    fn new_framebuffer_is_dirty() {
+        let mut data = [0; 4 * 3 * 2];
+        let mut fb = Framebuffer::<Rgb565>::new(&mut data, Size::new(4, 3));
+        assert_eq!(fb.take_dirty(), Some(rect(0, 0, 4, 3)));
+        assert_eq!(fb.take_dirty(), None);
+    }
+
+    #[test]
+    fn dirty_area_is_bounding_box_of_drawing() {
+        let mut data = [0; 8 * 8 * 2];
+        let mut fb = Framebuffer::<Rgb565>::new(&mut data, Size::new(8, 8));
+        fb.take_dirty();
+        Pixel(Point::new(2, 5), Rgb565::RED).draw(&mut fb).unwrap();
+        Pixel(Point::new(6, 1), Rgb565::RED).draw(&mut fb).unwrap();
+        assert_eq!(fb.take_dirty(), Some(rect(2, 1, 5, 5)));
+
+        // Out of bounds pixels and areas don't mark anything outside.
+        Pixel(Point::new(9, 9), Rgb565::RED).draw(&mut fb).unwrap();
+        assert_eq!(fb.take_dirty(), None);
+        fb.fill_solid(&rect(6, 6, 10, 10), Rgb565::RED).unwrap();
+        assert_eq!(fb.take_dirty(), Some(rect(6, 6, 2, 2)));
+    }
+
+    #[test]
+    fn flush_dirty_sends_only_dirty_area() {
+        let mut data = [0; 4 * 4 * 2];
+        let mut fb = Framebuffer::<Rgb565>::new(&mut data, Size::new(4, 4));
+        fb.take_dirty();
+        fb.fill_solid(&rect(1, 1, 2, 2), Rgb565::RED).unwrap();
+        let mut display = Recorder::default();
+        block_on(display.flush_dirty(&mut fb, Point::new(10, 20)));
+        // The rows aren't consecutive in the framebuffer, so each is sent
+        // separately to the same area.
+        assert_eq!(display.areas(), [rect(11, 21, 2, 2); 2]);
+        assert_eq!(display.bytes(), [0xF8, 0].repeat(4));
+
+        // Nothing is sent once clean.
+        display.writes.clear();
+        block_on(display.flush_dirty(&mut fb, Point::zero()));
+        assert!(display.writes.is_empty());
+    }
+
+    #[test]
+    fn dirty_rows_bitmap() {
+        let mut data = [0; 8 * 8 * 2];
+        let mut fb = Framebuffer::<Rgb565>::new(&mut data, Size::new(8, 8));
+        assert_eq!(fb.take_dirty_rows(), 0xFF);
+        Pixel(Point::new(0, 1), Rgb565::RED).draw(&mut fb).unwrap();
+        fb.fill_solid(&rect(3, 5, 2, 2), Rgb565::RED).unwrap();
+        assert_eq!(fb.take_dirty_rows(), 0b0110_0010);
+        assert_eq!(fb.take_dirty_rows(), 0);
+    }
+
+    #[test]
+    fn row_mask_maps_rows_past_128_to_last_bit() {
+        assert_eq!(row_mask(&rect(0, 0, 1, 0)), 0);
+        assert_eq!(row_mask(&rect(0, 126, 1, 1)), 1 << 126);
+        assert_eq!(row_mask(&rect(0, 126, 1, 4)), 0b11 << 126);
+        assert_eq!(row_mask(&rect(0, 130, 1, 2)), 1 << 127);
+        assert_eq!(row_mask(&rect(0, 0, 1, 200)), u128::MAX);
+    }
+
+    #[test]
+    fn flush_dirty_rows_merges_consecutive_rows() {
+        let mut data = [0; 4 * 8];
+        let mut fb = Framebuffer::<Rgb332>::new(&mut data, Size::new(4, 8));
+        fb.take_dirty_rows();
+        let color = Rgb332::new(7, 0, 0);
+        Pixel(Point::new(0, 1), color).draw(&mut fb).unwrap();
+        Pixel(Point::new(3, 2), color).draw(&mut fb).unwrap();
+        Pixel(Point::new(1, 6), color).draw(&mut fb).unwrap();
+        let mut display = Recorder::default();
+        block_on(display.flush_dirty_rows(&mut fb, Point::new(0, 10)));
+        assert_eq!(display.areas(), [rect(0, 11, 4, 2), rect(0, 16, 4, 1)]);
+        assert_eq!(display.bytes().len(), 4 * 3);
+    }
 }
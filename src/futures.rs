@@ -0,0 +1,75 @@
+//! Minimal future combinators, to avoid depending on an executor crate.
+
+use core::future::{poll_fn, Future};
+use core::pin::pin;
+use core::task::Poll;
+
+pub(crate) enum Either<A, B> {
+    First(A),
+    Second(B),
+}
+
+/// Runs both futures until one of them completes, and drops the other one.
+/// The first future is polled first.
+pub(crate) async fn select<A, B>(a: A, b: B) -> Either<A::Output, B::Output>
+where
+    A: Future,
+    B: Future,
+{
+    let mut a = pin!(a);
+    let mut b = pin!(b);
+    poll_fn(|cx| {
+        if let Poll::Ready(x) = a.as_mut().poll(cx) {
+            return Poll::Ready(Either::First(x));
+        }
+        if let Poll::Ready(x) = b.as_mut().poll(cx) {
+            return Poll::Ready(Either::Second(x));
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+/// Runs both futures to completion and returns both outputs. The first
+/// future is polled first.
+pub(crate) async fn join<A, B>(a: A, b: B) -> (A::Output, B::Output)
+where
+    A: Future,
+    B: Future,
+{
+    let mut a = pin!(a);
+    let mut b = pin!(b);
+    let (mut a_out, mut b_out) = (None, None);
+    poll_fn(|cx| {
+        if a_out.is_none() {
+            if let Poll::Ready(x) = a.as_mut().poll(cx) {
+                a_out = Some(x);
+            }
+        }
+        if b_out.is_none() {
+            if let Poll::Ready(x) = b.as_mut().poll(cx) {
+                b_out = Some(x);
+            }
+        }
+        if a_out.is_some() && b_out.is_some() {
+            Poll::Ready((a_out.take().unwrap(), b_out.take().unwrap()))
+        } else {
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+/// Returns control to the executor once, letting other tasks run.
+pub(crate) async fn yield_now() {
+    let mut yielded = false;
+    poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+        yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
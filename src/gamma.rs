@@ -0,0 +1,126 @@
+use embedded_graphics_core::{
+    pixelcolor::Rgb565,
+    prelude::{OriginDimensions, Point},
+    primitives::Rectangle,
+};
+
+use crate::{expand::write_expanded, BitDepth, Framebuffer, TryAdapter, WritePixels};
+
+/// Lookup tables mapping each RGB565 channel to a corrected value, applied
+/// in software by [flush_with_gamma].
+///
+/// This is independent of the controller's gray scale table, and can be
+/// changed per flush, e.g. to show assets authored for sRGB monitors with a
+/// response closer to what they were designed for.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GammaLut {
+    pub r: [u8; 32],
+    pub g: [u8; 64],
+    pub b: [u8; 32],
+}
+
+impl GammaLut {
+    /// Leaves the colors unchanged.
+    pub const IDENTITY: Self = {
+        let mut lut = Self {
+            r: [0; 32],
+            g: [0; 64],
+            b: [0; 32],
+        };
+        let mut i = 0;
+        while i < 64 {
+            if i < 32 {
+                lut.r[i] = i as u8;
+                lut.b[i] = i as u8;
+            }
+            lut.g[i] = i as u8;
+            i += 1;
+        }
+        lut
+    };
+
+    /// Decodes sRGB values to linear light, for panels whose brightness is
+    /// proportional to the pixel value.
+    #[rustfmt::skip]
+    pub const SRGB_TO_LINEAR: Self = Self {
+        r: [
+            0, 0, 0, 0, 0, 1, 1, 1, 2, 2, 3, 3, 4, 5, 5, 6,
+            7, 8, 9, 10, 12, 13, 14, 16, 17, 19, 21, 23, 25, 27, 29, 31,
+        ],
+        g: [
+            0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 2, 2, 2, 3, 3,
+            3, 4, 4, 5, 5, 6, 6, 7, 8, 8, 9, 10, 10, 11, 12, 13,
+            14, 15, 16, 17, 18, 19, 20, 21, 23, 24, 25, 27, 28, 30, 31, 33,
+            34, 36, 37, 39, 41, 43, 44, 46, 48, 50, 52, 54, 56, 59, 61, 63,
+        ],
+        b: [
+            0, 0, 0, 0, 0, 1, 1, 1, 2, 2, 3, 3, 4, 5, 5, 6,
+            7, 8, 9, 10, 12, 13, 14, 16, 17, 19, 21, 23, 25, 27, 29, 31,
+        ],
+    };
+
+    /// Creates tables from a curve given on the 0-255 scale, e.g. one
+    /// computed with a floating point library or measured on the panel.
+    pub fn from_fn(curve: impl Fn(u8) -> u8) -> Self {
+        // Maps a channel value to 0-255 and back, rounding.
+        let table = |i: usize, max: usize| {
+            let v = curve(((i * 255 + max / 2) / max) as u8) as usize;
+            ((v * max + 127) / 255) as u8
+        };
+        Self {
+            r: core::array::from_fn(|i| table(i, 31)),
+            g: core::array::from_fn(|i| table(i, 63)),
+            b: core::array::from_fn(|i| table(i, 31)),
+        }
+    }
+
+    // Corrects a packed RGB565 value.
+    fn apply(&self, c: u16) -> u16 {
+        let r = u16::from(self.r[(c >> 11) as usize] & 0x1f);
+        let g = u16::from(self.g[((c >> 5) & 0x3f) as usize] & 0x3f);
+        let b = u16::from(self.b[(c & 0x1f) as usize] & 0x1f);
+        (r << 11) | (g << 5) | b
+    }
+}
+
+/// Sends the dirty area of a 16-bit framebuffer to the display, correcting
+/// the colors with the lookup tables on the way.
+///
+/// The framebuffer keeps the original colors, and no extra pass over it is
+/// needed: the corrected pixels are written into `buf` as many whole rows
+/// at a time as it can hold. The framebuffer's top-left corner is placed at
+/// `top_left` on the display, and the framebuffer is marked clean
+/// afterwards.
+///
+/// # Panics
+///
+/// If `buf` can't hold a row of the dirty area.
+pub async fn flush_with_gamma(
+    display: &mut (impl WritePixels + ?Sized),
+    fb: &mut Framebuffer<'_, Rgb565>,
+    top_left: Point,
+    lut: &GammaLut,
+    buf: &mut [u8],
+) {
+    let Some(dirty) = fb.take_dirty() else {
+        return;
+    };
+    let area = Rectangle::new(top_left + dirty.top_left, dirty.size);
+    let (x0, y0) = (dirty.top_left.x as usize, dirty.top_left.y as usize);
+    let width = fb.size().width as usize;
+    let data = fb.data();
+    let Ok(()) = write_expanded(
+        &mut TryAdapter(display),
+        area,
+        BitDepth::Sixteen,
+        buf,
+        |y, dst| {
+            let src = &data[((y0 + y as usize) * width + x0) * 2..];
+            for (d, s) in dst.chunks_mut(2).zip(src.chunks(2)) {
+                let c = lut.apply(u16::from_be_bytes([s[0], s[1]]));
+                d.copy_from_slice(&c.to_be_bytes());
+            }
+        },
+    )
+    .await;
+}
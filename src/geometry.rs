@@ -0,0 +1,21 @@
+//! Rectangle helpers missing from `embedded-graphics-core`.
+
+use embedded_graphics_core::primitives::Rectangle;
+
+/// Returns the bounding box of both rectangles. Zero-sized rectangles are
+/// ignored.
+pub(crate) fn union(a: Rectangle, b: Rectangle) -> Rectangle {
+    match (a.bottom_right(), b.bottom_right()) {
+        (Some(a_br), Some(b_br)) => Rectangle::with_corners(
+            a.top_left.component_min(b.top_left),
+            a_br.component_max(b_br),
+        ),
+        (Some(_), None) => a,
+        (None, _) => b,
+    }
+}
+
+/// Whether the rectangles have at least one pixel in common.
+pub(crate) fn overlaps(a: &Rectangle, b: &Rectangle) -> bool {
+    !a.intersection(b).is_zero_sized()
+}
@@ -0,0 +1,58 @@
+//! GIF playback using [tinygif](https://crates.io/crates/tinygif).
+
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{Point, Size},
+    pixelcolor::{Rgb565, RgbColor},
+    primitives::Rectangle,
+    Drawable,
+};
+use embedded_hal_async::delay::DelayNs;
+use tinygif::Gif;
+
+use crate::{flush_bands, BandTarget, TryAdapter, WritePixels};
+
+/// Plays all frames of a GIF once, with its top-left corner at `top_left`.
+///
+/// The frames are decoded into two band buffers and sent with
+/// [`flush_bands`], so no framebuffer of the full image size is needed; each
+/// buffer must hold `band_height` rows of the image in 16-bit color. Every
+/// band decodes the frame from the start, so taller bands trade RAM for
+/// speed. Each frame is shown for its delay, in addition to the transfer
+/// time.
+///
+/// Frames are drawn over black rather than over the previous frame, so GIFs
+/// that rely on transparency to keep parts of the previous frame must be
+/// exported with full frames.
+///
+/// # Panics
+///
+/// If `band_height` is zero or the buffers are too small.
+pub async fn play_gif<D>(
+    display: &mut D,
+    delay: &mut impl DelayNs,
+    gif: &Gif<'_, Rgb565>,
+    top_left: Point,
+    buffers: [&mut [u8]; 2],
+    band_height: u32,
+) where
+    D: WritePixels + ?Sized,
+{
+    let area = Rectangle::new(top_left, Size::new(gif.width().into(), gif.height().into()));
+    let [a, b] = buffers;
+    for frame in gif.frames() {
+        let Ok(()) = flush_bands(
+            &mut TryAdapter(display),
+            [&mut *a, &mut *b],
+            area,
+            band_height,
+            |fb, band| {
+                let mut target = BandTarget::new(fb, band.top_left - top_left);
+                let _ = target.clear(Rgb565::BLACK);
+                let _ = frame.draw(&mut target);
+            },
+        )
+        .await;
+        delay.delay_ms(u32::from(frame.delay_centis) * 10).await;
+    }
+}
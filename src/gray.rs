@@ -0,0 +1,27 @@
+use embedded_graphics_core::pixelcolor::{Gray4, Gray8};
+
+use crate::{color::ColorMath, PackedFramebuffer};
+
+/// Framebuffer with 4-bit gray levels, taking a quarter of the RAM of a
+/// 16-bit framebuffer.
+///
+/// Send it with [`flush_packed`](crate::flush_packed) and a
+/// [`gray_palette`] of 16 entries.
+pub type Gray4Framebuffer<'a> = PackedFramebuffer<'a, Gray4>;
+
+/// Framebuffer with 8-bit gray levels, e.g. for camera or thermal sensor
+/// data.
+///
+/// Send it with [`flush_packed`](crate::flush_packed) and a
+/// [`gray_palette`] of 256 entries.
+pub type Gray8Framebuffer<'a> = PackedFramebuffer<'a, Gray8>;
+
+/// Returns a palette of `N` levels evenly spaced from black to `tint`.
+///
+/// With white as the tint, the levels are plain grays; other tints give
+/// e.g. the look of an amber or green monochrome screen. Pass 16 levels for
+/// a [`Gray4Framebuffer`] and 256 for a [`Gray8Framebuffer`].
+pub fn gray_palette<C: ColorMath, const N: usize>(tint: C) -> [C; N] {
+    let max = N.saturating_sub(1).max(1);
+    core::array::from_fn(|i| tint.scale((i * 255 / max) as u8))
+}
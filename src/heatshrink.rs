@@ -0,0 +1,201 @@
+//! Images compressed with [heatshrink].
+//!
+//! Heatshrink is an LZSS variant designed for embedded systems. Compress the
+//! raw pixel data (in the same format as [`Framebuffer`](crate::Framebuffer)
+//! data) on the host, e.g. `heatshrink -e -w 8 -l 4 in.raw out.hs`, and pass
+//! the same window and lookahead sizes to the [Decoder].
+//!
+//! [heatshrink]: https://github.com/atomicobject/heatshrink
+
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::{BitDepth, WritePixels};
+
+/// Streaming heatshrink decoder.
+///
+/// Only needs a window buffer of `2^window_sz2` bytes, plus an output buffer
+/// for the decoded pixels.
+pub struct Decoder<'w> {
+    window: &'w mut [u8],
+    window_sz2: u8,
+    lookahead_sz2: u8,
+}
+
+impl<'w> Decoder<'w> {
+    /// Creates a decoder with the given window buffer and compression
+    /// parameters (`-w` and `-l` options of the `heatshrink` tool).
+    ///
+    /// # Panics
+    ///
+    /// If the window buffer is smaller than `2^window_sz2` bytes, or the
+    /// parameters are out of the range supported by heatshrink.
+    pub fn new(window: &'w mut [u8], window_sz2: u8, lookahead_sz2: u8) -> Self {
+        assert!((4..=15).contains(&window_sz2));
+        assert!(3 <= lookahead_sz2 && lookahead_sz2 < window_sz2);
+        assert!(window.len() >= 1 << window_sz2);
+        Self {
+            window,
+            window_sz2,
+            lookahead_sz2,
+        }
+    }
+
+    /// Decompresses the data and sends it to the given area of the display.
+    ///
+    /// The pixels are decoded into `buf`, which is sent whenever it's full.
+    /// Decoding stops when the area is filled or the data ends.
+    ///
+    /// # Panics
+    ///
+    /// If `buf` can't hold at least one pixel.
+    pub async fn write_pixels(
+        &mut self,
+        display: &mut (impl WritePixels + ?Sized),
+        data: &[u8],
+        bit_depth: BitDepth,
+        area: Rectangle,
+        buf: &mut [u8],
+    ) {
+        let bpp = bit_depth.bytes();
+        let buf_len = buf.len() - buf.len() % bpp;
+        assert!(buf_len > 0);
+        let mask = (1usize << self.window_sz2) - 1;
+        self.window[..=mask].fill(0);
+
+        let mut bits = BitReader { data, pos: 0 };
+        let mut remaining = area.size.width as usize * area.size.height as usize * bpp;
+        let mut head = 0;
+        let mut filled = 0;
+        while remaining > 0 {
+            // Either a literal byte, or a reference back into the window.
+            let (offset, count) = match bits.read(1) {
+                Some(1) => match bits.read(8) {
+                    Some(byte) => {
+                        self.window[head & mask] = byte as u8;
+                        (0, 1)
+                    }
+                    None => break,
+                },
+                Some(_) => match (bits.read(self.window_sz2), bits.read(self.lookahead_sz2)) {
+                    (Some(index), Some(count)) => (index as usize + 1, count as usize + 1),
+                    _ => break,
+                },
+                None => break,
+            };
+            for _ in 0..count.min(remaining) {
+                let b = self.window[head.wrapping_sub(offset) & mask];
+                self.window[head & mask] = b;
+                head = head.wrapping_add(1);
+                buf[filled] = b;
+                filled += 1;
+                if filled == buf_len {
+                    display.write_pixels(&buf[..filled], bit_depth, area).await;
+                    filled = 0;
+                }
+            }
+            remaining -= count.min(remaining);
+        }
+        let whole = filled - filled % bpp;
+        if whole > 0 {
+            display.write_pixels(&buf[..whole], bit_depth, area).await;
+        }
+    }
+}
+
+// Reads bits MSB-first, as written by the heatshrink encoder.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, count: u8) -> Option<u16> {
+        let count = count as usize;
+        if self.pos + count > self.data.len() * 8 {
+            return None;
+        }
+        let mut value = 0;
+        for _ in 0..count {
+            let bit = (self.data[self.pos / 8] >> (7 - self.pos % 8)) & 1;
+            value = (value << 1) | bit as u16;
+            self.pos += 1;
+        }
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics_core::prelude::{Point, Size};
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::test_util::{block_on, Recorder};
+
+    // Packs the (value, bit count) fields of the tokens MSB-first, padding
+    // the last byte with zeros.
+    fn pack(tokens: &[&[(u16, u8)]]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut pos = 0;
+        for &(value, count) in tokens.iter().copied().flatten() {
+            for i in (0..count).rev() {
+                if pos % 8 == 0 {
+                    bytes.push(0);
+                }
+                bytes[pos / 8] |= (((value >> i) & 1) as u8) << (7 - pos % 8);
+                pos += 1;
+            }
+        }
+        bytes
+    }
+
+    fn literal(byte: u8) -> [(u16, u8); 2] {
+        [(1, 1), (byte.into(), 8)]
+    }
+
+    // A back-reference with window size 8 and lookahead size 4.
+    fn backref(offset: u16, count: u16) -> [(u16, u8); 3] {
+        [(0, 1), (offset - 1, 8), (count - 1, 4)]
+    }
+
+    fn decode(data: &[u8], bit_depth: BitDepth, pixels: u32, buf: &mut [u8]) -> Recorder {
+        let mut window = [0; 256];
+        let mut display = Recorder::default();
+        let area = Rectangle::new(Point::zero(), Size::new(pixels, 1));
+        block_on(Decoder::new(&mut window, 8, 4).write_pixels(
+            &mut display,
+            data,
+            bit_depth,
+            area,
+            buf,
+        ));
+        display
+    }
+
+    #[test]
+    fn literals_and_back_references() {
+        let data = pack(&[&literal(1), &literal(2), &backref(2, 4), &literal(9)]);
+        let display = decode(&data, BitDepth::Eight, 7, &mut [0; 4]);
+        assert_eq!(display.bytes(), [1, 2, 1, 2, 1, 2, 9]);
+        assert_eq!(display.writes.len(), 2);
+    }
+
+    #[test]
+    fn back_reference_before_start_reads_zeros() {
+        let data = pack(&[&literal(5), &backref(3, 2)]);
+        let display = decode(&data, BitDepth::Eight, 3, &mut [0; 8]);
+        assert_eq!(display.bytes(), [5, 0, 0]);
+    }
+
+    #[test]
+    fn stops_at_area_end_and_drops_partial_pixels() {
+        let data = pack(&[&literal(1), &backref(1, 16)]);
+        let display = decode(&data, BitDepth::Eight, 5, &mut [0; 8]);
+        assert_eq!(display.bytes(), [1; 5]);
+
+        // Three bytes make one and a half 16-bit pixels.
+        let data = pack(&[&literal(1), &literal(2), &literal(3)]);
+        let display = decode(&data, BitDepth::Sixteen, 4, &mut [0; 8]);
+        assert_eq!(display.bytes(), [1, 2]);
+    }
+}
@@ -0,0 +1,166 @@
+use embedded_graphics_core::{
+    image::{GetPixel, ImageDrawable},
+    pixelcolor::raw::ToBytes,
+    prelude::{DrawTarget, OriginDimensions, PixelColor, Point, PointsIter, Size},
+    primitives::Rectangle,
+};
+
+use crate::{
+    expand::write_expanded, framebuffer::read_pixel, BitDepth, Framebuffer, FramebufferOwned,
+    TryWritePixels,
+};
+
+impl<C: ToBytes> Framebuffer<'_, C> {
+    /// Returns a view of the contents as an image, e.g. to draw them into
+    /// another [`DrawTarget`] with `embedded-graphics`' `Image`.
+    ///
+    /// The data is in the same format as `ImageRaw<C, BigEndian>`.
+    pub fn as_image(&self) -> FramebufferImage<'_, C> {
+        FramebufferImage {
+            data: self.data(),
+            size: self.size(),
+            _color: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<C, const W: usize, const H: usize, const B: usize> FramebufferOwned<C, W, H>
+where
+    C: ToBytes<Bytes = [u8; B]>,
+{
+    /// See [Framebuffer::as_image].
+    pub fn as_image(&self) -> FramebufferImage<'_, C> {
+        FramebufferImage {
+            data: self.data(),
+            size: self.size(),
+            _color: core::marker::PhantomData,
+        }
+    }
+}
+
+/// Framebuffer contents as an [`ImageDrawable`].
+///
+/// See [Framebuffer::as_image].
+#[derive(Clone, Copy)]
+pub struct FramebufferImage<'a, C> {
+    data: &'a [u8],
+    size: Size,
+    _color: core::marker::PhantomData<C>,
+}
+
+impl<'a, C: ToBytes> FramebufferImage<'a, C> {
+    /// Creates an image from big-endian pixel data of the given size, e.g.
+    /// a background stored in flash.
+    ///
+    /// Panics if the data is too small for the size.
+    pub fn new(data: &'a [u8], size: Size) -> Self {
+        let len = size.width as usize * size.height as usize * core::mem::size_of::<C::Bytes>();
+        Self {
+            data: &data[..len],
+            size,
+            _color: core::marker::PhantomData,
+        }
+    }
+
+    // Returns the bytes of the pixel, if it's within the image.
+    pub(crate) fn pixel_bytes(&self, p: Point) -> Option<&'a [u8]> {
+        let bpp = core::mem::size_of::<C::Bytes>();
+        let (Ok(x), Ok(y)) = (u32::try_from(p.x), u32::try_from(p.y)) else {
+            return None;
+        };
+        if x >= self.size.width || y >= self.size.height {
+            return None;
+        }
+        let offset = (y as usize * self.size.width as usize + x as usize) * bpp;
+        Some(&self.data[offset..offset + bpp])
+    }
+}
+
+impl<C> OriginDimensions for FramebufferImage<'_, C> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl<C> GetPixel for FramebufferImage<'_, C>
+where
+    C: PixelColor + ToBytes + From<C::Raw>,
+{
+    type Color = C;
+
+    fn pixel(&self, p: Point) -> Option<C> {
+        read_pixel(self.data, self.size, p)
+    }
+}
+
+impl<C> ImageDrawable for FramebufferImage<'_, C>
+where
+    C: PixelColor + ToBytes + From<C::Raw>,
+{
+    type Color = C;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        self.draw_sub_image(target, &Rectangle::new(Point::zero(), self.size))
+    }
+
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        // Like `ImageRaw`, don't draw anything if the area isn't entirely
+        // within the image.
+        let bounds = Rectangle::new(Point::zero(), self.size);
+        if area.is_zero_sized() || area.intersection(&bounds) != *area {
+            return Ok(());
+        }
+        let colors = area.points().filter_map(|p| self.pixel(p));
+        target.fill_contiguous(&Rectangle::new(Point::zero(), area.size), colors)
+    }
+}
+
+/// Sends an image to the display without drawing it into a framebuffer,
+/// with its top-left corner at `top_left`.
+///
+/// Works with any image that gives random access to its pixels, such as
+/// `ImageRaw` from `embedded-graphics` in either byte order, so static
+/// assets don't need RAM of their full size. The pixels are converted into
+/// `buf` as many whole rows at a time as it can hold, and each batch of
+/// rows is sent with a single transfer. Pixels the image doesn't have are
+/// sent as zeros.
+///
+/// Images whose data is already in the display format (e.g. `ImageRaw`
+/// with big-endian [`Rgb565`](embedded_graphics_core::pixelcolor::Rgb565))
+/// can be sent even faster by passing the data to
+/// [`TryWritePixels::try_write_pixels`] directly.
+///
+/// # Panics
+///
+/// If `buf` can't hold a row of the image.
+pub async fn flush_image<D, I>(
+    display: &mut D,
+    image: &I,
+    top_left: Point,
+    buf: &mut [u8],
+) -> Result<(), D::Error>
+where
+    D: TryWritePixels + ?Sized,
+    I: GetPixel + OriginDimensions,
+    I::Color: PixelColor + ToBytes,
+    <I::Color as ToBytes>::Bytes: AsRef<[u8]>,
+{
+    let bit_depth = BitDepth::of::<I::Color>();
+    let bpp = bit_depth.bytes();
+    let area = Rectangle::new(top_left, image.size());
+    write_expanded(display, area, bit_depth, buf, |y, row| {
+        for (x, dst) in row.chunks_mut(bpp).enumerate() {
+            match image.pixel(Point::new(x as i32, y as i32)) {
+                Some(c) => dst.copy_from_slice(c.to_be_bytes().as_ref()),
+                None => dst.fill(0),
+            }
+        }
+    })
+    .await
+}
@@ -0,0 +1,44 @@
+use embedded_graphics_core::{
+    pixelcolor::raw::{RawData, RawU4},
+    prelude::PixelColor,
+};
+
+use crate::PackedFramebuffer;
+
+/// Palette index with 16 possible values.
+///
+/// Colors of an [`IndexedFramebuffer`], mapped to real colors by the
+/// palette passed to [`flush_packed`](crate::flush_packed).
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Index4(RawU4);
+
+impl Index4 {
+    /// Creates a palette index; only the lower 4 bits are used.
+    pub const fn new(index: u8) -> Self {
+        Self(RawU4::new(index & 0x0F))
+    }
+
+    pub fn index(&self) -> u8 {
+        self.0.into_inner()
+    }
+}
+
+impl PixelColor for Index4 {
+    type Raw = RawU4;
+}
+
+impl From<RawU4> for Index4 {
+    fn from(data: RawU4) -> Self {
+        Self(data)
+    }
+}
+
+impl From<Index4> for RawU4 {
+    fn from(data: Index4) -> RawU4 {
+        data.0
+    }
+}
+
+/// Framebuffer with 4-bit palette indices, taking a quarter of the RAM of
+/// a 16-bit framebuffer.
+pub type IndexedFramebuffer<'a> = PackedFramebuffer<'a, Index4>;
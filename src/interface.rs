@@ -0,0 +1,198 @@
+use core::convert::Infallible;
+
+use embedded_hal::digital::{ErrorType, OutputPin};
+use embedded_hal_async::spi::{SpiBus, SpiDevice};
+
+use crate::Error;
+
+/// Transport for the command and data bytes sent to the display.
+///
+/// The controller tells commands from pixel data by the level of its D/C
+/// input, so an interface is a write-only bus plus a way to switch between
+/// the two. [`SpiInterface`] is the 4-wire SPI setup that [`Ssd1331::new`]
+/// uses; other transports, or mocks recording the command stream in tests,
+/// can be passed to [`Ssd1331::with_interface`].
+///
+/// Errors are reported as the driver's [`Error`], typically
+/// [`Error::Pin`] for the D/C pin and [`Error::Spi`] for the bus.
+///
+/// [`Ssd1331::new`]: crate::Ssd1331::new
+/// [`Ssd1331::with_interface`]: crate::Ssd1331::with_interface
+#[allow(async_fn_in_trait)]
+pub trait DataCommand {
+    type Error;
+
+    /// Sends the bytes as commands (and their arguments).
+    async fn send_commands(&mut self, commands: &[u8]) -> Result<(), Self::Error>;
+
+    /// Sends the bytes as pixel data.
+    async fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Forgets any state cached about the bus, e.g. the level of the D/C
+    /// pin, so that the next transfer sets it up from scratch.
+    fn reset(&mut self) {}
+}
+
+/// Output pin for a display input that isn't connected to the MCU.
+///
+/// Use it for CS when it's tied low, e.g. as the CS pin of an
+/// `ExclusiveDevice`, or for RST when it's tied high (or to the MCU reset).
+/// Setting it does nothing. As its error type is [`Infallible`], it can
+/// only stand in next to pins that can't fail either.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct NoPin;
+
+impl ErrorType for NoPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for NoPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Interface over an SPI device and a D/C pin.
+///
+/// The D/C pin is only toggled when switching between commands and data.
+pub struct SpiInterface<DC, SPI> {
+    dc: DC,
+    spi: SPI,
+    // Last level set on the DC pin, if known: true for data, false for
+    // commands.
+    dc_data: Option<bool>,
+}
+
+impl<DC, SPI> SpiInterface<DC, SPI>
+where
+    DC: OutputPin,
+    SPI: SpiDevice,
+{
+    pub fn new(dc: DC, spi: SPI) -> Self {
+        Self {
+            dc,
+            spi,
+            dc_data: None,
+        }
+    }
+
+    /// Returns the pin and the SPI device.
+    pub fn release(self) -> (DC, SPI) {
+        (self.dc, self.spi)
+    }
+}
+
+impl<DC: OutputPin, SPI> SpiInterface<DC, SPI> {
+    fn set_dc(&mut self, data: bool) -> Result<(), DC::Error> {
+        if self.dc_data != Some(data) {
+            self.dc_data = None;
+            if data {
+                self.dc.set_high()?;
+            } else {
+                self.dc.set_low()?;
+            }
+            self.dc_data = Some(data);
+        }
+        Ok(())
+    }
+}
+
+impl<DC, SPI> DataCommand for SpiInterface<DC, SPI>
+where
+    DC: OutputPin,
+    SPI: SpiDevice,
+{
+    type Error = Error<DC::Error, SPI::Error>;
+
+    async fn send_commands(&mut self, commands: &[u8]) -> Result<(), Self::Error> {
+        self.set_dc(false).map_err(Error::Pin)?;
+        self.spi.write(commands).await.map_err(Error::Spi)
+    }
+
+    async fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.set_dc(true).map_err(Error::Pin)?;
+        self.spi.write(data).await.map_err(Error::Spi)
+    }
+
+    fn reset(&mut self) {
+        self.dc_data = None;
+    }
+}
+
+/// Interface over an SPI bus, a CS pin and a D/C pin, for a display that
+/// is the only device on the bus.
+///
+/// CS is asserted before the first transfer and then left low, so there's
+/// no per-transfer overhead of an `SpiDevice` wrapper. The bus is flushed
+/// after each transfer, before the D/C pin can change.
+pub struct SpiBusInterface<DC, CS, BUS> {
+    spi: SpiInterface<DC, BUS>,
+    cs: CS,
+    selected: bool,
+}
+
+impl<DC, CS, BUS> SpiBusInterface<DC, CS, BUS>
+where
+    DC: OutputPin,
+    CS: OutputPin<Error = DC::Error>,
+    BUS: SpiBus,
+{
+    pub fn new(dc: DC, cs: CS, bus: BUS) -> Self {
+        Self {
+            spi: SpiInterface {
+                dc,
+                spi: bus,
+                dc_data: None,
+            },
+            cs,
+            selected: false,
+        }
+    }
+
+    /// Returns the pins and the bus. CS is left as it was, i.e. low after
+    /// any transfer.
+    pub fn release(self) -> (DC, CS, BUS) {
+        let (dc, bus) = (self.spi.dc, self.spi.spi);
+        (dc, self.cs, bus)
+    }
+
+    async fn write(
+        &mut self,
+        data: bool,
+        bytes: &[u8],
+    ) -> Result<(), Error<DC::Error, BUS::Error>> {
+        if !self.selected {
+            self.cs.set_low().map_err(Error::Pin)?;
+            self.selected = true;
+        }
+        self.spi.set_dc(data).map_err(Error::Pin)?;
+        self.spi.spi.write(bytes).await.map_err(Error::Spi)?;
+        self.spi.spi.flush().await.map_err(Error::Spi)
+    }
+}
+
+impl<DC, CS, BUS> DataCommand for SpiBusInterface<DC, CS, BUS>
+where
+    DC: OutputPin,
+    CS: OutputPin<Error = DC::Error>,
+    BUS: SpiBus,
+{
+    type Error = Error<DC::Error, BUS::Error>;
+
+    async fn send_commands(&mut self, commands: &[u8]) -> Result<(), Self::Error> {
+        self.write(false, commands).await
+    }
+
+    async fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.write(true, data).await
+    }
+
+    fn reset(&mut self) {
+        self.spi.dc_data = None;
+        self.selected = false;
+    }
+}
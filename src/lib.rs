@@ -2,25 +2,129 @@
 
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(test)]
+extern crate std;
+
 use command::Command;
+use core::convert::Infallible;
 use embedded_graphics_core::pixelcolor::raw::ToBytes;
+use embedded_graphics_core::pixelcolor::Rgb565;
 use embedded_graphics_core::prelude::{Dimensions, OriginDimensions, PixelColor, Point, Size};
 use embedded_graphics_core::primitives::Rectangle;
 use embedded_hal::digital::OutputPin;
 use embedded_hal_async::delay::DelayNs;
-use embedded_hal_async::spi::SpiDevice;
+use embedded_hal_async::spi::{SpiBus, SpiDevice};
+use futures::{select, yield_now, Either};
 use heapless::Vec;
 
+#[cfg(feature = "primitives")]
+mod accel;
+pub mod animation;
+mod band_target;
+mod bands;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "bmp")]
+mod bmp;
+#[cfg(feature = "alloc")]
+mod boxed;
+mod chart;
+pub mod color;
 mod command;
+mod display_list;
+mod dither;
+mod double_buffer;
+mod dump;
+mod expand;
+#[cfg(feature = "framebuf")]
+mod framebuf;
 mod framebuffer;
+mod futures;
+mod gamma;
+mod geometry;
+#[cfg(feature = "gif")]
+mod gif;
+mod gray;
+#[cfg(feature = "heatshrink")]
+pub mod heatshrink;
+mod image;
+mod index4;
+mod interface;
+mod ops;
+mod packed;
+mod palette;
+mod parallel;
+mod progress;
 mod rgb332;
+pub mod rle;
+mod scheduler;
+#[cfg(feature = "embassy-sync")]
+mod shared;
+mod sprite;
+mod staged;
+#[cfg(feature = "embedded-io-async")]
+mod stream;
+#[cfg(test)]
+mod test_util;
+pub mod text;
+mod tiled;
+mod tilemap;
+#[cfg(feature = "u8g2-fonts")]
+mod u8g2;
+mod view;
 
-pub use framebuffer::Framebuffer;
-pub use rgb332::Rgb332;
+pub use band_target::{draw_in_bands, BandTarget};
+pub use bands::flush_bands;
+#[cfg(feature = "bmp")]
+pub use bmp::flush_bmp;
+#[cfg(feature = "alloc")]
+pub use boxed::BoxedDisplay;
+pub use chart::StripChart;
+pub use display_list::{DisplayList, DisplayListFull};
+pub use dither::{dither_to_rgb332, flush_rgb565_as_rgb332, flush_rgb565_as_rgb332_diffused};
+pub use double_buffer::{DoubleBuffer, FlushHandle, RenderHandle, SharedDoubleBuffer};
+#[cfg(feature = "framebuf")]
+pub use framebuf::flush_framebuf;
+#[cfg(feature = "draw-stats")]
+pub use framebuffer::DrawStats;
+pub use framebuffer::{Framebuffer, FramebufferMut, FramebufferOwned};
+pub use gamma::{flush_with_gamma, GammaLut};
+#[cfg(feature = "gif")]
+pub use gif::play_gif;
+pub use gray::{gray_palette, Gray4Framebuffer, Gray8Framebuffer};
+pub use image::{flush_image, FramebufferImage};
+pub use index4::{Index4, IndexedFramebuffer};
+pub use interface::{DataCommand, NoPin, SpiBusInterface, SpiInterface};
+pub use ops::Rotation;
+pub use packed::{flush_packed, MonoFramebuffer, PackedFramebuffer};
+pub use palette::Palette;
+pub use parallel::{DataBus8, ParallelInterface, ParallelMode};
+pub use progress::ProgressBar;
+pub use rgb332::{flush_rgb332_as_rgb565, Rgb332};
+pub use scheduler::FlushScheduler;
+#[cfg(feature = "embassy-sync")]
+pub use shared::{Region, SharedDisplay};
+pub use sprite::{Background, Sprite, SpriteSheet};
+pub use staged::{StagedTarget, StagingFull};
+#[cfg(feature = "embedded-io-async")]
+pub use stream::write_pixels_from_reader;
+pub use tiled::TiledDisplay;
+pub use tilemap::TileMap;
+#[cfg(feature = "u8g2-fonts")]
+pub use u8g2::U8g2TextRenderer;
+pub use view::FramebufferView;
 
+/// Width of the controller's RAM, and of most panels; see [PanelGeometry].
 pub const DISPLAY_WIDTH: u32 = 96;
+/// Height of the controller's RAM, and of most panels; see [PanelGeometry].
 pub const DISPLAY_HEIGHT: u32 = 64;
 
+// Size of the stack buffer used when pixel data needs to be converted on the
+// way to the display. A full 16-bit frame takes 24 transfers.
+const CONVERSION_BUF_SIZE: usize = 512;
+
 /// Number of bits per pixel in a data transfer.
 ///
 /// The display internally supports BGR order and alternative 16-bit color
@@ -42,6 +146,15 @@ impl BitDepth {
             Self::Sixteen => 2,
         }
     }
+
+    // Bit depth of the big-endian byte representation of a color.
+    pub(crate) const fn of<C: ToBytes>() -> Self {
+        match core::mem::size_of::<C::Bytes>() {
+            1 => Self::Eight,
+            2 => Self::Sixteen,
+            _ => panic!(),
+        }
+    }
 }
 
 /// Row- or column-major order of pixels for a data transfer.
@@ -92,6 +205,36 @@ pub enum RowInterleave {
     Enabled = 0x20,
 }
 
+/// Size of the panel driven by the controller, in the default orientation.
+///
+/// Most modules use the full 96x64 pixels the controller supports, but some
+/// clones have fewer rows (e.g. 96x32) or columns. The panel is assumed to
+/// be wired to the first columns and rows of the controller. Only the rows
+/// of the panel are scanned, and the driver's bounds follow the geometry.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PanelGeometry {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PanelGeometry {
+    /// The full size supported by the controller, 96x64.
+    pub const FULL: Self = Self::new(DISPLAY_WIDTH, DISPLAY_HEIGHT);
+
+    /// Panics if the size is empty or larger than 96x64.
+    pub const fn new(width: u32, height: u32) -> Self {
+        assert!(width > 0 && width <= DISPLAY_WIDTH);
+        assert!(height > 0 && height <= DISPLAY_HEIGHT);
+        Self { width, height }
+    }
+}
+
+impl Default for PanelGeometry {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
 /// Describes the mapping between the display memory and the physical pixels.
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Config {
@@ -99,6 +242,7 @@ pub struct Config {
     pub column_direction: ColumnDirection,
     pub row_direction: RowDirection,
     pub row_interleave: RowInterleave,
+    pub geometry: PanelGeometry,
 }
 
 impl Default for Config {
@@ -113,11 +257,17 @@ impl Default for Config {
             column_direction: ColumnDirection::LeftToRight,
             row_direction: RowDirection::Normal,
             row_interleave: RowInterleave::Enabled,
+            geometry: PanelGeometry::FULL,
         }
     }
 }
 
 impl Config {
+    /// Returns the configuration for a panel of the given size.
+    pub const fn with_geometry(self, geometry: PanelGeometry) -> Self {
+        Self { geometry, ..self }
+    }
+
     /// For orientation rotated 90 degrees counter-clockwise from the default.
     pub fn ccw90() -> Self {
         Self {
@@ -125,6 +275,7 @@ impl Config {
             column_direction: ColumnDirection::LeftToRight,
             row_direction: RowDirection::Reversed,
             row_interleave: RowInterleave::Enabled,
+            geometry: PanelGeometry::FULL,
         }
     }
 
@@ -135,6 +286,7 @@ impl Config {
             column_direction: ColumnDirection::RightToLeft,
             row_direction: RowDirection::Reversed,
             row_interleave: RowInterleave::Enabled,
+            geometry: PanelGeometry::FULL,
         }
     }
 
@@ -145,17 +297,153 @@ impl Config {
             column_direction: ColumnDirection::RightToLeft,
             row_direction: RowDirection::Normal,
             row_interleave: RowInterleave::Enabled,
+            geometry: PanelGeometry::FULL,
+        }
+    }
+}
+
+/// Contrast of each color channel, which sets the current of its subpixels.
+///
+/// Panels from different batches have visibly different white points; see
+/// [Ssd1331::set_color_balance].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ColorBalance {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Default for ColorBalance {
+    /// The power-on default is 0x80 for all channels. Lowering the G channel
+    /// seems to result in a better color balance on my display.
+    fn default() -> Self {
+        Self::new(0x80, 0x50, 0x80)
+    }
+}
+
+impl ColorBalance {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Shifts the white point towards warm (positive `warmth`) or cool
+    /// (negative) colors, by raising the red contrast and lowering the blue
+    /// one, or the other way around.
+    ///
+    /// At the ends of the range, the channels change by about half, which
+    /// makes for a single user-facing slider over a calibrated balance.
+    pub const fn with_temperature(self, warmth: i8) -> Self {
+        const fn adjust(value: u8, amount: i32) -> u8 {
+            let value = value as i32;
+            let adjusted = value + value * amount / 256;
+            if adjusted > 255 {
+                255
+            } else {
+                adjusted as u8
+            }
+        }
+        Self {
+            r: adjust(self.r, warmth as i32),
+            g: self.g,
+            b: adjust(self.b, -(warmth as i32)),
         }
     }
 }
 
+/// Kind of SPI transfer, see [Transfer].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TransferKind {
+    Command,
+    Data,
+}
+
+/// Describes an SPI transfer for the hook set with
+/// [Ssd1331::set_transfer_hook].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Transfer {
+    pub kind: TransferKind,
+    /// Number of bytes in this transfer.
+    pub len: usize,
+    /// The address window of the display RAM at the time of the transfer.
+    /// This is in RAM coordinates, which are the logical coordinates with X
+    /// and Y swapped in column-major modes.
+    pub area: Rectangle,
+    /// False before the transfer starts, true after it has completed.
+    pub done: bool,
+}
+
+/// Counters of the traffic sent to the display, see [Ssd1331::stats].
+///
+/// The counters wrap around on overflow.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Stats {
+    /// Number of pixel data bytes sent.
+    pub data_bytes: u32,
+    /// Number of command bytes sent, including the address window changes.
+    pub command_bytes: u32,
+    /// Number of SPI transfers, both command and data.
+    pub transfers: u32,
+    /// Number of times the address window was changed.
+    pub window_changes: u32,
+}
+
+// Time in microseconds the controller needs to complete an accelerated
+// command like ClearWindow or Copy on the area, with some margin. Clearing
+// the whole screen takes about 500 us.
+fn accelerated_command_us(area: Rectangle) -> u32 {
+    10 + area.size.width * area.size.height / 8
+}
+
 /// Error type for this driver.
 ///
-/// Currently only used to propagate errors from the HAL.
+/// Mostly used to propagate errors from the HAL.
 #[derive(Debug)]
 pub enum Error<PinE, SpiE> {
     Pin(PinE),
+    /// Error from the SPI device, or from the bus of another
+    /// [DataCommand] interface.
     Spi(SpiE),
+    /// The area passed to a pixel transfer is empty or not completely
+    /// contained within the display bounds. Nothing was sent.
+    InvalidArea,
+    /// The transfer didn't complete in the given time.
+    Timeout,
+    /// The length of the data passed to a transfer doesn't match what it
+    /// needs. Nothing was sent.
+    BufferSizeMismatch,
+    /// The last initialization of the display failed before the display was
+    /// configured, so it's in an unknown state. Nothing was sent; call
+    /// [Ssd1331::init] to recover.
+    NotInitialized,
+}
+
+impl<PinE: core::fmt::Debug, SpiE: core::fmt::Debug> core::fmt::Display for Error<PinE, SpiE> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Pin(e) => write!(f, "pin error: {e:?}"),
+            Self::Spi(e) => write!(f, "bus error: {e:?}"),
+            Self::InvalidArea => f.write_str("area not within the display bounds"),
+            Self::Timeout => f.write_str("transfer timed out"),
+            Self::BufferSizeMismatch => f.write_str("data length doesn't match the transfer"),
+            Self::NotInitialized => f.write_str("display not initialized"),
+        }
+    }
+}
+
+impl<PinE: core::fmt::Debug, SpiE: core::fmt::Debug> core::error::Error for Error<PinE, SpiE> {}
+
+#[cfg(feature = "defmt")]
+impl<PinE: defmt::Format, SpiE: defmt::Format> defmt::Format for Error<PinE, SpiE> {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Self::Pin(e) => defmt::write!(f, "pin error: {}", e),
+            Self::Spi(e) => defmt::write!(f, "bus error: {}", e),
+            Self::InvalidArea => defmt::write!(f, "area not within the display bounds"),
+            Self::Timeout => defmt::write!(f, "transfer timed out"),
+            Self::BufferSizeMismatch => defmt::write!(f, "data length doesn't match the transfer"),
+            Self::NotInitialized => defmt::write!(f, "display not initialized"),
+        }
+    }
 }
 
 /// The implementation of the driver.
@@ -169,35 +457,49 @@ pub enum Error<PinE, SpiE> {
 /// text one character at a time, or mix text and graphics areas.
 ///
 /// The driver dutifully propagates all errors from the HAL, but the display
-/// controller is stateful and the driver doesn't attempt to return it to a
-/// known good state after an error. You can call `init()` to hard-reset the
-/// display and reinitialize the driver after an error.
+/// controller is stateful and the driver only makes a limited attempt to
+/// return it to a known good state after an error: the next pixel transfer
+/// re-sends the address window and the bit depth (see [Ssd1331::resync]).
+/// You can call `init()` to hard-reset the display and reinitialize the
+/// driver after an error.
 ///
 /// [`embedded-graphics`]: https://crates.io/crates/embedded-graphics
-pub struct Ssd1331<RST, DC, SPI> {
+pub struct Ssd1331<RST, DI> {
     data_mapping: Config,
 
     rst: RST,
-    dc: DC,
-    spi: SPI,
+    interface: DI,
 
     bit_depth: BitDepth,
     area: Rectangle,
+    max_transfer_size: usize,
+    retries: u8,
 
+    // Whether the display state matches bit_depth and area, and the last data
+    // transfer has completed.
+    synced: bool,
+    // Whether the last initialization got as far as configuring the display.
+    initialized: bool,
+    in_transaction: bool,
     command_buf: Vec<u8, 16>,
+
+    color_balance: ColorBalance,
+    transfer_hook: Option<fn(&Transfer)>,
+    stats: Stats,
 }
 
-impl<RST, DC, SPI> OriginDimensions for Ssd1331<RST, DC, SPI> {
+impl<RST, DI> OriginDimensions for Ssd1331<RST, DI> {
     fn size(&self) -> Size {
+        let PanelGeometry { width, height } = self.data_mapping.geometry;
         if self.data_mapping.pixel_order == PixelOrder::RowMajor {
-            Size::new(DISPLAY_WIDTH, DISPLAY_HEIGHT)
+            Size::new(width, height)
         } else {
-            Size::new(DISPLAY_HEIGHT, DISPLAY_WIDTH)
+            Size::new(height, width)
         }
     }
 }
 
-impl<RST, DC, SPI, PinE, SpiE> Ssd1331<RST, DC, SPI>
+impl<RST, DC, SPI, PinE, SpiE> Ssd1331<RST, SpiInterface<DC, SPI>>
 where
     RST: OutputPin<Error = PinE>,
     DC: OutputPin<Error = PinE>,
@@ -208,7 +510,7 @@ where
     /// Requires GPIO output pins connected to RST and DC pins on the display,
     /// and a SPI device with SDO and SCK outputs connected to the display.
     /// The CS (chip select) pin of the display can be controlled by the SPI
-    /// device, or you can simply tie it low, and pass a DummyPin to the SPI
+    /// device, or you can simply tie it low, and pass a [NoPin] to the SPI
     /// device. SPI bus should be configured to MODE_0, MSB first (usually the
     /// default). Frequencies up to 50 MHz seem to work fine, even though the
     /// display datasheet specifies ~6 MHz max.
@@ -218,18 +520,114 @@ where
         dc: DC,
         spi: SPI,
         delay: &mut impl DelayNs,
+    ) -> Result<Self, Error<PinE, SpiE>> {
+        let interface = SpiInterface::new(dc, spi);
+        Self::create(data_mapping, rst, interface, delay, None).await
+    }
+
+    /// Same as [Self::new], but shows a splash screen instead of clearing
+    /// the display.
+    ///
+    /// See [Self::init_with_splash].
+    pub async fn new_with_splash(
+        data_mapping: Config,
+        rst: RST,
+        dc: DC,
+        spi: SPI,
+        delay: &mut impl DelayNs,
+        splash: &[u8],
+        bit_depth: BitDepth,
+    ) -> Result<Self, Error<PinE, SpiE>> {
+        let interface = SpiInterface::new(dc, spi);
+        Self::create(
+            data_mapping,
+            rst,
+            interface,
+            delay,
+            Some((splash, bit_depth)),
+        )
+        .await
+    }
+
+    /// Consumes the driver and returns the peripherals to you.
+    pub fn release(self) -> (RST, DC, SPI) {
+        let (dc, spi) = self.interface.release();
+        (self.rst, dc, spi)
+    }
+}
+
+impl<RST, DC, CS, BUS, PinE, SpiE> Ssd1331<RST, SpiBusInterface<DC, CS, BUS>>
+where
+    RST: OutputPin<Error = PinE>,
+    DC: OutputPin<Error = PinE>,
+    CS: OutputPin<Error = PinE>,
+    BUS: SpiBus<Error = SpiE>,
+{
+    /// Same as [Ssd1331::new], but takes the SPI bus and the pin connected
+    /// to CS, instead of an SPI device.
+    ///
+    /// For a display that is the only device on the bus. See
+    /// [SpiBusInterface].
+    pub async fn new_on_bus(
+        data_mapping: Config,
+        bus: BUS,
+        cs: CS,
+        rst: RST,
+        dc: DC,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, Error<PinE, SpiE>> {
+        let interface = SpiBusInterface::new(dc, cs, bus);
+        Self::create(data_mapping, rst, interface, delay, None).await
+    }
+
+    /// Consumes the driver and returns the peripherals to you.
+    pub fn release(self) -> (RST, DC, CS, BUS) {
+        let (dc, cs, bus) = self.interface.release();
+        (self.rst, dc, cs, bus)
+    }
+}
+
+impl<RST, DI, PinE, SpiE> Ssd1331<RST, DI>
+where
+    RST: OutputPin<Error = PinE>,
+    DI: DataCommand<Error = Error<PinE, SpiE>>,
+{
+    /// Same as [Ssd1331::new], but sends the commands and data through the
+    /// given interface, e.g. a different bus.
+    pub async fn with_interface(
+        data_mapping: Config,
+        rst: RST,
+        interface: DI,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, Error<PinE, SpiE>> {
+        Self::create(data_mapping, rst, interface, delay, None).await
+    }
+
+    async fn create(
+        data_mapping: Config,
+        rst: RST,
+        interface: DI,
+        delay: &mut impl DelayNs,
+        splash: Option<(&[u8], BitDepth)>,
     ) -> Result<Self, Error<PinE, SpiE>> {
         let mut d = Self {
             rst,
-            dc,
-            spi,
+            interface,
             data_mapping,
             bit_depth: BitDepth::Sixteen,
             area: Rectangle::zero(), // Just until init().
+            max_transfer_size: usize::MAX,
+            retries: 0,
+            synced: false,
+            initialized: false,
+            in_transaction: false,
             command_buf: Vec::new(),
+            color_balance: ColorBalance::default(),
+            transfer_hook: None,
+            stats: Stats::default(),
         };
 
-        d.init(delay).await?;
+        d.init_display(delay, splash).await?;
 
         Ok(d)
     }
@@ -239,9 +637,35 @@ where
     /// Also clears the display RAM. This will take a few milliseconds.
     /// Instances returned by [Self::new] are already initialized.
     pub async fn init(&mut self, delay: &mut impl DelayNs) -> Result<(), Error<PinE, SpiE>> {
+        self.init_display(delay, None).await
+    }
+
+    /// Hard-resets and re-initializes the display, showing a splash screen.
+    ///
+    /// The splash image is sent to the whole display before it's turned on,
+    /// so the first visible frame is the image rather than a cleared screen.
+    /// The data must cover the display in the current orientation, in the
+    /// given bit depth; any part it doesn't cover shows the old RAM
+    /// contents.
+    pub async fn init_with_splash(
+        &mut self,
+        delay: &mut impl DelayNs,
+        splash: &[u8],
+        bit_depth: BitDepth,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        self.init_display(delay, Some((splash, bit_depth))).await
+    }
+
+    async fn init_display(
+        &mut self,
+        delay: &mut impl DelayNs,
+        splash: Option<(&[u8], BitDepth)>,
+    ) -> Result<(), Error<PinE, SpiE>> {
         // Hold the display in reset for 1ms. Note that this does not seem to
         // clear the onboard RAM. The RST pin behaves as NRST (low level resets
         // the display).
+        self.synced = false;
+        self.initialized = false;
         self.rst.set_low().map_err(Error::Pin)?;
         delay.delay_ms(1).await;
         self.rst.set_high().map_err(Error::Pin)?;
@@ -250,30 +674,142 @@ where
         self.area = Rectangle::new(Point::zero(), Size::new(DISPLAY_WIDTH, DISPLAY_HEIGHT));
         self.bit_depth = BitDepth::Sixteen;
 
+        self.interface.reset();
+        self.in_transaction = false;
         self.command_buf.clear();
 
         self.send_commands(&[
             Command::RemapAndBitDepth(self.data_mapping, self.bit_depth),
+            Command::MultiplexRatio((self.data_mapping.geometry.height - 1) as u8),
             // Default is 15, results in grays saturating at about 50%.
             Command::MasterCurrent(5),
-            // Default is 0x80 for all. Lowering the G channel seems to result
-            // in a better color balance on my display. This should be a user
-            // setting.
-            Command::Contrast(0x80, 0x50, 0x80),
-            Command::ClearWindow(self.area),
-            Command::DisplayOn(true),
+            Command::Contrast(
+                self.color_balance.r,
+                self.color_balance.g,
+                self.color_balance.b,
+            ),
         ])
         .await?;
+        self.initialized = true;
 
-        // ClearWindow needs time to write to RAM.
-        delay.delay_ms(1).await;
+        if let Some((data, bit_depth)) = splash {
+            let area = self.bounding_box();
+            self.write_pixels_once(data, bit_depth, area).await?;
+        } else {
+            self.send_commands_now(&[Command::ClearWindow(self.area)])
+                .await?;
+            // ClearWindow needs time to write to RAM.
+            delay.delay_ms(1).await;
+        }
+        self.send_commands(&[Command::DisplayOn(true)]).await?;
 
+        self.synced = true;
         Ok(())
     }
 
-    /// Consumes the driver and returns the peripherals to you.
-    pub fn release(self) -> (RST, DC, SPI) {
-        (self.rst, self.dc, self.spi)
+    /// Returns the contrast of each color channel.
+    pub fn color_balance(&self) -> ColorBalance {
+        self.color_balance
+    }
+
+    /// Sets the contrast of each color channel, e.g. to adjust the white
+    /// point of the panel.
+    ///
+    /// The balance is kept across [Self::init]. The default is
+    /// [ColorBalance::default].
+    pub async fn set_color_balance(
+        &mut self,
+        balance: ColorBalance,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        self.send_commands(&[Command::Contrast(balance.r, balance.g, balance.b)])
+            .await?;
+        self.color_balance = balance;
+        Ok(())
+    }
+
+    /// Starts batching commands and data across calls.
+    ///
+    /// Until [Self::end_transaction] is called, commands that don't need to
+    /// be sent right away are queued in the driver and go out in a single
+    /// transfer, together with the address window setup of the next pixel
+    /// write. The DC pin is only toggled when switching between commands
+    /// and data. This reduces the per-call overhead when sending many small
+    /// areas, e.g. when rendering text one character at a time.
+    pub fn begin_transaction(&mut self) {
+        self.in_transaction = true;
+    }
+
+    /// Ends the transaction started by [Self::begin_transaction] and sends
+    /// any queued commands.
+    pub async fn end_transaction(&mut self) -> Result<(), Error<PinE, SpiE>> {
+        self.in_transaction = false;
+        self.flush_commands().await
+    }
+
+    /// Limits the number of bytes sent in a single SPI transfer.
+    ///
+    /// Some HALs can't transfer more than a certain number of bytes with a
+    /// single DMA request. With the limit set, larger pixel data is split into
+    /// several consecutive `write()` calls on the SPI device. There's no limit
+    /// by default.
+    ///
+    /// # Panics
+    ///
+    /// If `size` is zero.
+    pub fn set_max_transfer_size(&mut self, size: usize) {
+        assert!(size > 0);
+        self.max_transfer_size = size;
+    }
+
+    /// Sets how many times [Self::write_pixels] retries after a HAL error.
+    ///
+    /// Each retry re-sends the bit depth and the address window, followed by
+    /// all the data. That's only correct if the data starts at the top-left
+    /// corner of the area; when filling an area with several calls, a retried
+    /// call restarts at the beginning of the area. There are no retries by
+    /// default.
+    pub fn set_retries(&mut self, retries: u8) {
+        self.retries = retries;
+    }
+
+    /// Sets a function to call before and after each SPI transfer.
+    ///
+    /// The hook is called synchronously from the driver, so it should be
+    /// quick, e.g. record a timestamp or toggle a debug pin.
+    pub fn set_transfer_hook(&mut self, hook: Option<fn(&Transfer)>) {
+        self.transfer_hook = hook;
+    }
+
+    /// Returns the traffic counters accumulated since the driver was created
+    /// or since the last [Self::reset_stats].
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Resets all traffic counters to zero.
+    pub fn reset_stats(&mut self) {
+        self.stats = Stats::default();
+    }
+
+    /// Consumes the driver and returns the reset pin and the interface.
+    pub fn release_interface(self) -> (RST, DI) {
+        (self.rst, self.interface)
+    }
+
+    /// Returns a [`DrawTarget`] that draws directly to the display, staging
+    /// the pixels in `scratch` until [StagedTarget::commit].
+    ///
+    /// Lets synchronous drawing code target the display when a framebuffer
+    /// of the drawn area doesn't fit in RAM. Unlike a framebuffer, only the
+    /// drawn pixels are sent, but each staged region costs a transfer.
+    ///
+    /// [`DrawTarget`]: embedded_graphics_core::draw_target::DrawTarget
+    pub fn draw_target<'a, C>(&'a mut self, scratch: &'a mut [u8]) -> StagedTarget<'a, RST, DI, C>
+    where
+        C: PixelColor + ToBytes,
+        C::Bytes: AsRef<[u8]>,
+    {
+        StagedTarget::new(self, scratch)
     }
 
     /// Sends the data to the given area of the display's frame buffer.
@@ -286,108 +822,967 @@ where
     /// this method and passing the same `area`. Sending more data than fits
     /// in the area will wrap around and overwrite the beginning of the area.
     ///
-    /// # Panics
+    /// Returns [Error::InvalidArea] without sending anything if the area is
+    /// empty or not completely contained within the display bounds.
     ///
-    /// If the area is empty or not completely contained within the display
-    /// bounds.
+    /// HAL errors are retried as configured with [Self::set_retries].
     pub async fn write_pixels(
         &mut self,
         data: &[u8],
         bit_depth: BitDepth,
         area: Rectangle,
     ) -> Result<(), Error<PinE, SpiE>> {
-        assert!(self.bounding_box().contains(area.top_left));
-        assert!(self.bounding_box().contains(area.bottom_right().unwrap()));
-        assert!(self.command_buf.is_empty());
-        if self.bit_depth != bit_depth {
+        let mut retries = self.retries;
+        loop {
+            match self.write_pixels_once(data, bit_depth, area).await {
+                Err(Error::Pin(_) | Error::Spi(_)) if retries > 0 => retries -= 1,
+                result => return result,
+            }
+        }
+    }
+
+    async fn write_pixels_once(
+        &mut self,
+        data: &[u8],
+        bit_depth: BitDepth,
+        area: Rectangle,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        self.start_data(bit_depth, area).await?;
+        self.write_data(data).await?;
+        self.end_data();
+        Ok(())
+    }
+
+    /// Same as [Self::write_pixels], but gives up if the transfer takes longer
+    /// than `timeout_us` microseconds.
+    ///
+    /// Returns [Error::Timeout] if the transfer hasn't completed in time, e.g.
+    /// because of a bad connection or a stuck DMA. The `delay` is only used
+    /// for the timeout, and is polled concurrently with the transfer. The
+    /// interrupted transfer is abandoned, and the next one re-sends the
+    /// address window.
+    pub async fn write_pixels_with_timeout(
+        &mut self,
+        data: &[u8],
+        bit_depth: BitDepth,
+        area: Rectangle,
+        delay: &mut impl DelayNs,
+        timeout_us: u32,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        match select(
+            self.write_pixels(data, bit_depth, area),
+            delay.delay_us(timeout_us),
+        )
+        .await
+        {
+            Either::First(result) => result,
+            Either::Second(()) => Err(Error::Timeout),
+        }
+    }
+
+    /// Sends the data to the part of the given area that is on the display.
+    ///
+    /// Unlike [Self::write_pixels], the area can extend past the display
+    /// bounds, e.g. for a sprite that is partially off-screen. The data must
+    /// be in row-major order and start at the top-left corner of the area;
+    /// bytes of the pixels outside the display are skipped. If the whole area
+    /// is off-screen, nothing is sent.
+    ///
+    /// Unless the area is clipped only vertically, the visible part of each
+    /// row is sent in a separate SPI transfer.
+    pub async fn write_pixels_clipped(
+        &mut self,
+        data: &[u8],
+        bit_depth: BitDepth,
+        area: Rectangle,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        let visible = area.intersection(&self.bounding_box());
+        if visible.is_zero_sized() {
+            return Ok(());
+        }
+        let bpp = bit_depth.bytes();
+        let row_len = area.size.width as usize * bpp;
+        let skip_x = (visible.top_left.x - area.top_left.x) as usize * bpp;
+        let skip_y = (visible.top_left.y - area.top_left.y) as usize;
+        let visible_len = visible.size.width as usize * bpp;
+
+        self.start_data(bit_depth, visible).await?;
+        if visible_len == row_len {
+            let start = (skip_y * row_len).min(data.len());
+            let end = (start + visible.size.height as usize * row_len).min(data.len());
+            self.write_data(&data[start..end]).await?;
+        } else {
+            for row in skip_y..skip_y + visible.size.height as usize {
+                let start = row * row_len + skip_x;
+                if start >= data.len() {
+                    break;
+                }
+                let end = (start + visible_len).min(data.len());
+                self.write_data(&data[start..end]).await?;
+            }
+        }
+        self.end_data();
+        Ok(())
+    }
+
+    /// Sends a batch of same-sized sprites, each to its own location.
+    ///
+    /// Each sprite's data must hold `size` pixels in the given bit depth. The
+    /// bit depth is set only once for the whole batch, so each sprite costs
+    /// just the address window command. Returns [Error::InvalidArea] without
+    /// sending anything if any of the sprites doesn't fit on the display, or
+    /// [Error::BufferSizeMismatch] if any of the data has the wrong length.
+    pub async fn write_sprites(
+        &mut self,
+        sprites: &[(&[u8], Point)],
+        size: Size,
+        bit_depth: BitDepth,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        let len = size.width as usize * size.height as usize * bit_depth.bytes();
+        for &(data, top_left) in sprites {
+            self.check_area(Rectangle::new(top_left, size))?;
+            if data.len() != len {
+                return Err(Error::BufferSizeMismatch);
+            }
+        }
+        for &(data, top_left) in sprites {
+            self.write_pixels(data, bit_depth, Rectangle::new(top_left, size))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Sets a single pixel of the display to the given color.
+    ///
+    /// Each call sends a few command bytes to address the pixel, so this is
+    /// only suitable for sparse updates like cursors or plot points.
+    pub async fn set_pixel(
+        &mut self,
+        point: Point,
+        color: Rgb565,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        self.write_pixels(
+            &color.to_be_bytes(),
+            BitDepth::Sixteen,
+            Rectangle::new(point, Size::new(1, 1)),
+        )
+        .await
+    }
+
+    /// Copies an area of the display to another location, using the
+    /// controller's copy command.
+    ///
+    /// Both `area` and its copy at `dst` must be within the display bounds,
+    /// otherwise [Error::InvalidArea] is returned without sending anything.
+    /// Only a few command bytes are sent, so this is much cheaper than
+    /// resending the pixels, e.g. to scroll a part of the screen. The
+    /// controller needs time to move the pixels, so this waits using `delay`
+    /// for an estimate based on the area size before returning.
+    pub async fn copy_area(
+        &mut self,
+        area: Rectangle,
+        dst: Point,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        let dst = Rectangle::new(dst, area.size);
+        self.check_area(area)?;
+        self.check_area(dst)?;
+        let (ram_src, ram_dst) = (self.command_area(area), self.command_area(dst));
+        self.send_commands_now(&[Command::Copy(ram_src, ram_dst.top_left)])
+            .await?;
+        delay.delay_us(accelerated_command_us(area)).await;
+        Ok(())
+    }
+
+    /// Draws a rectangle with the controller's drawing command.
+    ///
+    /// The one-pixel border is drawn in the `border` color, and the interior
+    /// is filled with `fill`, if given. Only about a dozen command bytes are
+    /// sent regardless of the size, e.g. to update a progress bar without
+    /// sending any pixels. Returns [Error::InvalidArea] without sending
+    /// anything if the area is empty or not within the display bounds. The
+    /// controller needs time to draw, so this waits using `delay` for an
+    /// estimate based on the area size before returning.
+    pub async fn draw_rectangle(
+        &mut self,
+        area: Rectangle,
+        border: Rgb565,
+        fill: Option<Rgb565>,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        self.check_area(area)?;
+        let ram_area = self.command_area(area);
+        self.send_commands_now(&[
+            Command::SetFillEnabled(fill.is_some()),
+            Command::DrawRectangle(ram_area, border, fill.unwrap_or(border)),
+        ])
+        .await?;
+        delay.delay_us(accelerated_command_us(area)).await;
+        Ok(())
+    }
+
+    /// Draws a one-pixel line between the two points (both included) with
+    /// the controller's drawing command.
+    ///
+    /// Returns [Error::InvalidArea] without sending anything if either point
+    /// is outside the display. See [Self::draw_rectangle] for the rest.
+    pub async fn draw_line(
+        &mut self,
+        start: Point,
+        end: Point,
+        color: Rgb565,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        let (a, b) = (
+            Rectangle::new(start, Size::new(1, 1)),
+            Rectangle::new(end, Size::new(1, 1)),
+        );
+        self.check_area(a)?;
+        self.check_area(b)?;
+        let (ram_a, ram_b) = (self.command_area(a), self.command_area(b));
+        self.send_commands_now(&[Command::DrawLine(ram_a.top_left, ram_b.top_left, color)])
+            .await?;
+        delay
+            .delay_us(accelerated_command_us(Rectangle::with_corners(start, end)))
+            .await;
+        Ok(())
+    }
+
+    /// Sends 16-bit pixels to the given area of the display's frame buffer.
+    ///
+    /// Same as [Self::write_pixels] with [BitDepth::Sixteen], but takes
+    /// native `u16` values (e.g. from [`Rgb565::into_storage`]) and converts
+    /// them to big-endian while sending. The conversion goes through a
+    /// 512-byte buffer on the stack, so the data is sent in several SPI
+    /// transfers.
+    ///
+    /// Returns [Error::InvalidArea] if the area is invalid, see
+    /// [Self::write_pixels]. HAL errors are retried as configured with
+    /// [Self::set_retries]; a retry re-sends all the data.
+    ///
+    /// [`Rgb565::into_storage`]: embedded_graphics_core::prelude::IntoStorage
+    pub async fn write_pixels_u16(
+        &mut self,
+        data: &[u16],
+        area: Rectangle,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        self.write_converted(data, area, CONVERSION_BUF_SIZE / 2, |src, dst| {
+            for (i, p) in src.iter().enumerate() {
+                dst[i * 2..i * 2 + 2].copy_from_slice(&p.to_be_bytes());
+            }
+            src.len() * 2
+        })
+        .await
+    }
+
+    /// Sends little-endian 16-bit pixels to the given area of the display's
+    /// frame buffer.
+    ///
+    /// Same as [Self::write_pixels] with [BitDepth::Sixteen], but swaps the
+    /// bytes of each pixel while sending. Useful for assets in `rgb565le`
+    /// format, as produced by many image conversion tools. The conversion
+    /// goes through a 512-byte buffer on the stack, so the data is sent in
+    /// several SPI transfers.
+    ///
+    /// Returns [Error::InvalidArea] if the area is invalid, see
+    /// [Self::write_pixels], and [Error::BufferSizeMismatch] if the data
+    /// length is odd. HAL errors are retried as configured with
+    /// [Self::set_retries]; a retry re-sends all the data.
+    pub async fn write_pixels_le(
+        &mut self,
+        data: &[u8],
+        area: Rectangle,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        if !data.len().is_multiple_of(2) {
+            return Err(Error::BufferSizeMismatch);
+        }
+        self.write_converted(data, area, CONVERSION_BUF_SIZE, |src, dst| {
+            for i in (0..src.len()).step_by(2) {
+                dst[i] = src[i + 1];
+                dst[i + 1] = src[i];
+            }
+            src.len()
+        })
+        .await
+    }
+
+    // Sends 16-bit data to the area in chunks of `chunk_len` items, each
+    // converted to bytes in a stack buffer by `convert`, which returns the
+    // number of bytes written. HAL errors restart the whole area, as in
+    // Self::write_pixels.
+    async fn write_converted<T>(
+        &mut self,
+        data: &[T],
+        area: Rectangle,
+        chunk_len: usize,
+        convert: impl Fn(&[T], &mut [u8]) -> usize,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        let mut buf = [0u8; CONVERSION_BUF_SIZE];
+        let mut retries = self.retries;
+        loop {
+            match self
+                .write_converted_once(data, area, chunk_len, &convert, &mut buf)
+                .await
+            {
+                Err(Error::Pin(_) | Error::Spi(_)) if retries > 0 => retries -= 1,
+                result => return result,
+            }
+        }
+    }
+
+    async fn write_converted_once<T>(
+        &mut self,
+        data: &[T],
+        area: Rectangle,
+        chunk_len: usize,
+        convert: &impl Fn(&[T], &mut [u8]) -> usize,
+        buf: &mut [u8],
+    ) -> Result<(), Error<PinE, SpiE>> {
+        self.start_data(BitDepth::Sixteen, area).await?;
+        for chunk in data.chunks(chunk_len) {
+            let n = convert(chunk, buf);
+            self.write_data(&buf[..n]).await?;
+        }
+        self.end_data();
+        Ok(())
+    }
+
+    // Sends the data, split into transfers of at most `max_transfer_size`
+    // bytes.
+    async fn write_data(&mut self, data: &[u8]) -> Result<(), Error<PinE, SpiE>> {
+        for chunk in data.chunks(self.max_transfer_size) {
+            self.notify(TransferKind::Data, chunk.len(), false);
+            self.interface.send_data(chunk).await?;
+            self.stats.data_bytes = self.stats.data_bytes.wrapping_add(chunk.len() as u32);
+            self.stats.transfers = self.stats.transfers.wrapping_add(1);
+            self.notify(TransferKind::Data, chunk.len(), true);
+        }
+        Ok(())
+    }
+
+    fn notify(&self, kind: TransferKind, len: usize, done: bool) {
+        if let Some(hook) = self.transfer_hook {
+            hook(&Transfer {
+                kind,
+                len,
+                area: self.area,
+                done,
+            });
+        }
+    }
+
+    /// Makes the next pixel transfer re-send the address window and the bit
+    /// depth to the display.
+    ///
+    /// The driver skips these commands when they match the previous transfer.
+    /// If a transfer future is dropped before completion (e.g. on a timeout
+    /// in `select`), the driver does this automatically, as well as after an
+    /// error. This method is useful if the display state may have been
+    /// changed outside the driver's knowledge, e.g. by a glitch on the bus.
+    pub fn resync(&mut self) {
+        self.synced = false;
+        self.interface.reset();
+    }
+
+    // Sets up the bit depth and the address window for a data transfer, and
+    // sends any queued commands. Until end_data() is called, the
+    // address window is assumed to be partially filled, so that an
+    // interrupted transfer is not continued by the next one.
+    async fn start_data(
+        &mut self,
+        bit_depth: BitDepth,
+        area: Rectangle,
+    ) -> Result<(), Error<PinE, SpiE>> {
+        self.check_area(area)?;
+        let synced = core::mem::replace(&mut self.synced, false);
+        if !synced || self.bit_depth != bit_depth {
             self.bit_depth = bit_depth;
-            assert!(Command::RemapAndBitDepth(self.data_mapping, self.bit_depth)
-                .push(&mut self.command_buf));
+            self.queue_command(Command::RemapAndBitDepth(self.data_mapping, self.bit_depth))
+                .await?;
         }
         let ram_area = self.ram_area(area);
-        if self.area != ram_area {
+        if !synced || self.area != ram_area {
             self.area = ram_area;
-            assert!(Command::AddressRectangle(self.area).push(&mut self.command_buf));
+            self.queue_command(Command::AddressRectangle(self.area))
+                .await?;
+            self.stats.window_changes = self.stats.window_changes.wrapping_add(1);
         }
-        self.flush_commands().await?;
-        self.dc.set_high().map_err(Error::Pin)?;
-        self.spi.write(data).await.map_err(Error::Spi)?;
+        self.flush_commands().await
+    }
 
-        Ok(())
+    // Checks that the display is initialized, and the area is non-empty and
+    // within the display bounds.
+    fn check_area(&self, area: Rectangle) -> Result<(), Error<PinE, SpiE>> {
+        if !self.initialized {
+            return Err(Error::NotInitialized);
+        }
+        let bounds = self.bounding_box();
+        match area.bottom_right() {
+            Some(br) if bounds.contains(area.top_left) && bounds.contains(br) => Ok(()),
+            _ => Err(Error::InvalidArea),
+        }
+    }
+
+    fn end_data(&mut self) {
+        self.synced = true;
     }
 
     // Returns display RAM rectangle for the given rectangle on the logical
     // display. The display controller takes into account the X/Y mirroring
     // settings, but the axis remain X and Y regardless of the pixel order.
+    // With mirrored columns, a panel narrower than the controller shows the
+    // last RAM columns, so the area is shifted there.
     fn ram_area(&self, area: Rectangle) -> Rectangle {
-        if self.data_mapping.pixel_order == PixelOrder::RowMajor {
+        let mut ram_area = if self.data_mapping.pixel_order == PixelOrder::RowMajor {
             area
         } else {
             Rectangle::new(
                 Point::new(area.top_left.y, area.top_left.x),
                 Size::new(area.size.height, area.size.width),
             )
+        };
+        if self.data_mapping.column_direction == ColumnDirection::RightToLeft {
+            ram_area.top_left.x += (DISPLAY_WIDTH - self.data_mapping.geometry.width) as i32;
         }
+        ram_area
     }
 
+    // Returns the display RAM rectangle for the given rectangle on the
+    // logical display, for the commands that take RAM coordinates directly.
+    // Unlike pixel data, these don't go through the column direction setting,
+    // so the columns need to be mirrored here.
+    fn command_area(&self, area: Rectangle) -> Rectangle {
+        let ram_area = self.ram_area(area);
+        if self.data_mapping.column_direction == ColumnDirection::LeftToRight {
+            return ram_area;
+        }
+        let right = ram_area.top_left.x + ram_area.size.width as i32;
+        Rectangle::new(
+            Point::new(DISPLAY_WIDTH as i32 - right, ram_area.top_left.y),
+            ram_area.size,
+        )
+    }
+
+    // Sends the commands, or just queues them during a transaction.
     async fn send_commands(&mut self, commands: &[Command]) -> Result<(), Error<PinE, SpiE>> {
         for command in commands {
-            if command.push(&mut self.command_buf) {
-                continue;
-            }
+            self.queue_command(*command).await?;
+        }
+        if !self.in_transaction {
+            self.flush_commands().await?;
+        }
+        Ok(())
+    }
+
+    // Sends the commands right away, even in a transaction. Used before
+    // waiting for the controller to execute them.
+    async fn send_commands_now(&mut self, commands: &[Command]) -> Result<(), Error<PinE, SpiE>> {
+        for command in commands {
+            self.queue_command(*command).await?;
+        }
+        self.flush_commands().await
+    }
+
+    // Appends the command to the buffer, flushing the buffer first if the
+    // command doesn't fit.
+    async fn queue_command(&mut self, command: Command) -> Result<(), Error<PinE, SpiE>> {
+        if !command.push(&mut self.command_buf) {
             self.flush_commands().await?;
             assert!(command.push(&mut self.command_buf));
         }
-        self.flush_commands().await?;
         Ok(())
     }
 
     async fn flush_commands(&mut self) -> Result<(), Error<PinE, SpiE>> {
         if !self.command_buf.is_empty() {
-            self.dc.set_low().map_err(Error::Pin)?;
-            self.spi
-                .write(&self.command_buf)
-                .await
-                .map_err(Error::Spi)?;
+            let len = self.command_buf.len();
+            self.notify(TransferKind::Command, len, false);
+            self.interface.send_commands(&self.command_buf).await?;
             self.command_buf.clear();
+            self.stats.command_bytes = self.stats.command_bytes.wrapping_add(len as u32);
+            self.stats.transfers = self.stats.transfers.wrapping_add(1);
+            self.notify(TransferKind::Command, len, true);
         }
         Ok(())
     }
 }
 
+/// Selects every other row of a framebuffer for an interlaced update.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Field {
+    /// Rows 0, 2, 4, ...
+    Even,
+    /// Rows 1, 3, 5, ...
+    Odd,
+}
+
+impl Field {
+    /// Returns the other field, for alternating between frames.
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Even => Self::Odd,
+            Self::Odd => Self::Even,
+        }
+    }
+}
+
 /// Convenience trait to hide details of the driver type.
 ///
 /// Once the display driver is created, only the error type depends on the HAL
 /// types used for the implementation. For the use cases where panic on error
 /// is acceptable, we can ignore the type parameters.
+///
+/// The helpers here are thin wrappers around the ones of [TryWritePixels],
+/// which return the errors instead.
+///
+/// Helpers should take the display as `&mut (impl WritePixels + ?Sized)`,
+/// like the ones in this crate, so that callers keep ownership of it. There's
+/// no implementation for `&mut T`, as `OriginDimensions` can't be implemented
+/// for references outside of `embedded-graphics-core`.
 #[allow(async_fn_in_trait)]
 pub trait WritePixels: OriginDimensions {
     /// See [Ssd1331::write_pixels].
     async fn write_pixels(&mut self, data: &[u8], bit_depth: BitDepth, area: Rectangle);
 
-    /// Transfers the contents of the framebuffer to the display.
+    /// See [Ssd1331::set_pixel].
+    async fn set_pixel(&mut self, point: Point, color: Rgb565) {
+        self.write_pixels(
+            &color.to_be_bytes(),
+            BitDepth::Sixteen,
+            Rectangle::new(point, Size::new(1, 1)),
+        )
+        .await
+    }
+
+    /// See [Ssd1331::write_sprites].
+    async fn write_sprites(&mut self, sprites: &[(&[u8], Point)], size: Size, bit_depth: BitDepth) {
+        for &(data, top_left) in sprites {
+            self.write_pixels(data, bit_depth, Rectangle::new(top_left, size))
+                .await;
+        }
+    }
+
+    /// See [TryWritePixels::try_write_rows].
+    async fn write_rows(
+        &mut self,
+        area: Rectangle,
+        bit_depth: BitDepth,
+        row_buf: &mut [u8],
+        fill: impl FnMut(u32, &mut [u8]),
+    ) {
+        let Ok(()) = TryAdapter(self)
+            .try_write_rows(area, bit_depth, row_buf, fill)
+            .await;
+    }
+
+    /// See [TryWritePixels::try_flush].
     async fn flush<C>(&mut self, fb: &Framebuffer<'_, C>, top_left: Point)
     where
         C: PixelColor + ToBytes,
     {
-        self.write_pixels(
+        let Ok(()) = TryAdapter(self).try_flush(fb, top_left).await;
+    }
+
+    /// See [TryWritePixels::try_flush_dirty].
+    async fn flush_dirty<C>(&mut self, fb: &mut Framebuffer<'_, C>, top_left: Point)
+    where
+        C: PixelColor + ToBytes,
+    {
+        let Ok(()) = TryAdapter(self).try_flush_dirty(fb, top_left).await;
+    }
+
+    /// See [TryWritePixels::try_flush_dirty_rows].
+    async fn flush_dirty_rows<C>(&mut self, fb: &mut Framebuffer<'_, C>, top_left: Point)
+    where
+        C: PixelColor + ToBytes,
+    {
+        let Ok(()) = TryAdapter(self).try_flush_dirty_rows(fb, top_left).await;
+    }
+
+    /// See [TryWritePixels::try_flush_region].
+    async fn flush_region<C>(
+        &mut self,
+        fb: &Framebuffer<'_, C>,
+        fb_rect: Rectangle,
+        top_left: Point,
+    ) where
+        C: PixelColor + ToBytes,
+    {
+        let Ok(()) = TryAdapter(self)
+            .try_flush_region(fb, fb_rect, top_left)
+            .await;
+    }
+
+    /// See [TryWritePixels::try_flush_interlaced].
+    async fn flush_interlaced<C>(&mut self, fb: &Framebuffer<'_, C>, top_left: Point, field: Field)
+    where
+        C: PixelColor + ToBytes,
+    {
+        let Ok(()) = TryAdapter(self)
+            .try_flush_interlaced(fb, top_left, field)
+            .await;
+    }
+
+    /// See [TryWritePixels::try_flush_diff].
+    async fn flush_diff<C>(
+        &mut self,
+        current: &Framebuffer<'_, C>,
+        previous: &Framebuffer<'_, C>,
+        top_left: Point,
+    ) where
+        C: PixelColor + ToBytes,
+    {
+        let Ok(()) = TryAdapter(self)
+            .try_flush_diff(current, previous, top_left)
+            .await;
+    }
+
+    /// See [TryWritePixels::try_flush_changed_rows].
+    async fn flush_changed_rows<C>(
+        &mut self,
+        fb: &Framebuffer<'_, C>,
+        hashes: &mut [u32],
+        top_left: Point,
+    ) where
+        C: PixelColor + ToBytes,
+    {
+        let Ok(()) = TryAdapter(self)
+            .try_flush_changed_rows(fb, hashes, top_left)
+            .await;
+    }
+}
+
+/// Fallible version of [WritePixels], for code that needs to handle errors.
+///
+/// Fallible displays like [Ssd1331] implement both traits, with
+/// [WritePixels] panicking on errors. Displays that only implement
+/// [WritePixels] can be passed to the helpers taking this trait through a
+/// [TryAdapter].
+#[allow(async_fn_in_trait)]
+pub trait TryWritePixels: OriginDimensions {
+    type Error;
+
+    /// See [Ssd1331::write_pixels].
+    async fn try_write_pixels(
+        &mut self,
+        data: &[u8],
+        bit_depth: BitDepth,
+        area: Rectangle,
+    ) -> Result<(), Self::Error>;
+
+    /// Fills and sends the area one row at a time.
+    ///
+    /// The `fill` callback is called for each row with the row index within
+    /// the area (from 0) and a buffer of the row size to fill with pixel data
+    /// of the given depth. Only the row buffer needs to be in RAM. After each
+    /// row, the task yields to the executor, so that a large update doesn't
+    /// hold up other tasks.
+    ///
+    /// # Panics
+    ///
+    /// If `row_buf` is smaller than a row of the area.
+    async fn try_write_rows(
+        &mut self,
+        area: Rectangle,
+        bit_depth: BitDepth,
+        row_buf: &mut [u8],
+        mut fill: impl FnMut(u32, &mut [u8]),
+    ) -> Result<(), Self::Error> {
+        let row = &mut row_buf[..area.size.width as usize * bit_depth.bytes()];
+        for y in 0..area.size.height {
+            fill(y, row);
+            self.try_write_pixels(row, bit_depth, area).await?;
+            yield_now().await;
+        }
+        Ok(())
+    }
+
+    /// Transfers the contents of the framebuffer to the display.
+    async fn try_flush<C>(
+        &mut self,
+        fb: &Framebuffer<'_, C>,
+        top_left: Point,
+    ) -> Result<(), Self::Error>
+    where
+        C: PixelColor + ToBytes,
+    {
+        self.try_write_pixels(
             fb.data(),
             fb.bit_depth(),
             Rectangle::new(top_left, fb.size()),
         )
         .await
     }
+
+    /// Transfers the part of the framebuffer drawn since the last call.
+    ///
+    /// Uses [Framebuffer::take_dirty] to find the area to send, so the
+    /// framebuffer is marked as clean afterwards.
+    async fn try_flush_dirty<C>(
+        &mut self,
+        fb: &mut Framebuffer<'_, C>,
+        top_left: Point,
+    ) -> Result<(), Self::Error>
+    where
+        C: PixelColor + ToBytes,
+    {
+        if let Some(dirty) = fb.take_dirty() {
+            self.try_flush_region(fb, dirty, top_left + dirty.top_left)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Transfers the full-width rows drawn to since the last call.
+    ///
+    /// Uses [Framebuffer::take_dirty_rows], so the framebuffer is marked as
+    /// clean afterwards. Each run of consecutive dirty rows is sent with a
+    /// single transfer, skipping the untouched rows in between.
+    async fn try_flush_dirty_rows<C>(
+        &mut self,
+        fb: &mut Framebuffer<'_, C>,
+        top_left: Point,
+    ) -> Result<(), Self::Error>
+    where
+        C: PixelColor + ToBytes,
+    {
+        let mut rows = fb.take_dirty_rows();
+        let width = fb.size().width;
+        while rows != 0 {
+            let first = rows.trailing_zeros();
+            let count = (rows >> first).trailing_ones();
+            rows &= !((u128::MAX >> (128 - count)) << first);
+            // The last bit stands for all the remaining rows.
+            let height = if first + count == 128 {
+                fb.size().height.saturating_sub(first)
+            } else {
+                count
+            };
+            let rect = Rectangle::new(Point::new(0, first as i32), Size::new(width, height));
+            self.try_flush_region(fb, rect, top_left + rect.top_left)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Transfers a rectangular part of the framebuffer to the display.
+    ///
+    /// The `fb_rect` is in framebuffer coordinates, and is clipped to the
+    /// framebuffer bounds. Its top-left corner is placed at `top_left` on the
+    /// display. Unless the rectangle spans the full width of the framebuffer,
+    /// each row is sent in a separate transfer.
+    async fn try_flush_region<C>(
+        &mut self,
+        fb: &Framebuffer<'_, C>,
+        fb_rect: Rectangle,
+        top_left: Point,
+    ) -> Result<(), Self::Error>
+    where
+        C: PixelColor + ToBytes,
+    {
+        let rect = fb_rect.intersection(&fb.bounding_box());
+        if rect.is_zero_sized() {
+            return Ok(());
+        }
+        let area = Rectangle::new(top_left + (rect.top_left - fb_rect.top_left), rect.size);
+        let bpp = fb.bit_depth().bytes();
+        let stride = fb.size().width as usize * bpp;
+        let row_len = rect.size.width as usize * bpp;
+        let first = rect.top_left.y as usize * stride + rect.top_left.x as usize * bpp;
+        if row_len == stride {
+            let len = rect.size.height as usize * stride;
+            self.try_write_pixels(&fb.data()[first..first + len], fb.bit_depth(), area)
+                .await?;
+            return Ok(());
+        }
+        for row in 0..rect.size.height as usize {
+            let start = first + row * stride;
+            self.try_write_pixels(&fb.data()[start..start + row_len], fb.bit_depth(), area)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Transfers every other row of the framebuffer to the display.
+    ///
+    /// Alternating the `field` between frames (see [Field::toggled]) halves
+    /// the amount of data sent per frame, at the cost of showing each frame
+    /// only partially. Every row needs its own address window, which adds a
+    /// few command bytes per row.
+    async fn try_flush_interlaced<C>(
+        &mut self,
+        fb: &Framebuffer<'_, C>,
+        top_left: Point,
+        field: Field,
+    ) -> Result<(), Self::Error>
+    where
+        C: PixelColor + ToBytes,
+    {
+        let first = match field {
+            Field::Even => 0,
+            Field::Odd => 1,
+        };
+        let size = fb.size();
+        for y in (first..size.height as i32).step_by(2) {
+            let row = Rectangle::new(Point::new(0, y), Size::new(size.width, 1));
+            self.try_flush_region(fb, row, top_left + row.top_left)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Transfers only the parts of the framebuffer that differ from
+    /// `previous`.
+    ///
+    /// The `previous` framebuffer should have the same size and hold what's
+    /// currently on the display at `top_left`. Consecutive rows with changes
+    /// are grouped together, and for each group, the columns between the
+    /// leftmost and the rightmost changed pixel are sent with
+    /// [Self::try_flush_region].
+    ///
+    /// # Panics
+    ///
+    /// If the framebuffers have different sizes.
+    async fn try_flush_diff<C>(
+        &mut self,
+        current: &Framebuffer<'_, C>,
+        previous: &Framebuffer<'_, C>,
+        top_left: Point,
+    ) -> Result<(), Self::Error>
+    where
+        C: PixelColor + ToBytes,
+    {
+        assert!(current.size() == previous.size());
+        let bpp = current.bit_depth().bytes();
+        let width = current.size().width as usize;
+        let rows = current.rows().zip(previous.rows());
+        // Columns range and first row of the group of changed rows.
+        let mut group: Option<(usize, usize, usize)> = None;
+        let group_rect = |(x0, x1, y0): (usize, usize, usize), y1: usize| {
+            Rectangle::new(
+                Point::new(x0 as i32, y0 as i32),
+                Size::new((x1 - x0 + 1) as u32, (y1 - y0) as u32),
+            )
+        };
+        for (y, (cur, prev)) in rows.enumerate() {
+            let differs = |x: &usize| cur[x * bpp..(x + 1) * bpp] != prev[x * bpp..(x + 1) * bpp];
+            let span = (0..width)
+                .find(differs)
+                .map(|first| (first, (first..width).rfind(differs).unwrap()));
+            group = match (group, span) {
+                (Some((g0, g1, gy)), Some((x0, x1))) => Some((g0.min(x0), g1.max(x1), gy)),
+                (None, Some((x0, x1))) => Some((x0, x1, y)),
+                (Some(g), None) => {
+                    let r = group_rect(g, y);
+                    self.try_flush_region(current, r, top_left + r.top_left)
+                        .await?;
+                    None
+                }
+                (None, None) => None,
+            };
+        }
+        if let Some(g) = group {
+            let r = group_rect(g, current.size().height as usize);
+            self.try_flush_region(current, r, top_left + r.top_left)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Transfers the rows of the framebuffer whose hash differs from the one
+    /// in `hashes`, and updates the hashes.
+    ///
+    /// A lighter alternative to [Self::try_flush_diff]: the caller keeps one
+    /// `u32` per row instead of the previous frame. The hashes are computed
+    /// with [Framebuffer::row_hash]; only the rows whose hash differs from
+    /// the stored one are sent, so the first call should start from hashes
+    /// that don't match, or follow a full [Self::try_flush]. Consecutive changed
+    /// rows are sent in a single transfer.
+    ///
+    /// # Panics
+    ///
+    /// If `hashes` has fewer entries than the framebuffer has rows.
+    async fn try_flush_changed_rows<C>(
+        &mut self,
+        fb: &Framebuffer<'_, C>,
+        hashes: &mut [u32],
+        top_left: Point,
+    ) -> Result<(), Self::Error>
+    where
+        C: PixelColor + ToBytes,
+    {
+        let size = fb.size();
+        let hashes = &mut hashes[..size.height as usize];
+        // First row of the run of changed rows.
+        let mut first = None;
+        for y in 0..=size.height {
+            let changed = y < size.height && {
+                let hash = fb.row_hash(y);
+                core::mem::replace(&mut hashes[y as usize], hash) != hash
+            };
+            match (first, changed) {
+                (None, true) => first = Some(y),
+                (Some(y0), false) => {
+                    let r = Rectangle::new(Point::new(0, y0 as i32), Size::new(size.width, y - y0));
+                    self.try_flush_region(fb, r, top_left + r.top_left).await?;
+                    first = None;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Adapts a [WritePixels] display to [TryWritePixels], to pass it to the
+/// helpers that return errors. The error type is [Infallible].
+pub struct TryAdapter<'a, T: ?Sized>(pub &'a mut T);
+
+impl<T: WritePixels + ?Sized> OriginDimensions for TryAdapter<'_, T> {
+    fn size(&self) -> Size {
+        self.0.size()
+    }
+}
+
+impl<T: WritePixels + ?Sized> TryWritePixels for TryAdapter<'_, T> {
+    type Error = Infallible;
+
+    async fn try_write_pixels(
+        &mut self,
+        data: &[u8],
+        bit_depth: BitDepth,
+        area: Rectangle,
+    ) -> Result<(), Self::Error> {
+        self.0.write_pixels(data, bit_depth, area).await;
+        Ok(())
+    }
 }
 
-impl<RST, DC, SPI, PinE, SpiE> WritePixels for Ssd1331<RST, DC, SPI>
+impl<RST, DI, PinE, SpiE> WritePixels for Ssd1331<RST, DI>
 where
     RST: OutputPin<Error = PinE>,
-    DC: OutputPin<Error = PinE>,
-    SPI: SpiDevice<Error = SpiE>,
+    DI: DataCommand<Error = Error<PinE, SpiE>>,
 {
     async fn write_pixels(&mut self, data: &[u8], bit_depth: BitDepth, area: Rectangle) {
-        self.write_pixels(data, bit_depth, area)
+        self.try_write_pixels(data, bit_depth, area)
             .await
             .unwrap_or_else(|_| panic!("write failed"))
     }
 }
+
+impl<RST, DI, PinE, SpiE> TryWritePixels for Ssd1331<RST, DI>
+where
+    RST: OutputPin<Error = PinE>,
+    DI: DataCommand<Error = Error<PinE, SpiE>>,
+{
+    type Error = Error<PinE, SpiE>;
+
+    async fn try_write_pixels(
+        &mut self,
+        data: &[u8],
+        bit_depth: BitDepth,
+        area: Rectangle,
+    ) -> Result<(), Self::Error> {
+        Ssd1331::write_pixels(self, data, bit_depth, area).await
+    }
+}
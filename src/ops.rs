@@ -0,0 +1,275 @@
+//! Operations that move pixel data around in a [`Framebuffer`].
+
+use embedded_graphics_core::{
+    pixelcolor::{raw::ToBytes, Rgb565},
+    prelude::{Dimensions, DrawTarget, OriginDimensions, PixelColor, Point, PointsIter, Size},
+    primitives::Rectangle,
+};
+
+use crate::{
+    color::{blend_raw, Opacity},
+    Framebuffer,
+};
+
+/// Counter-clockwise rotation for [Framebuffer::blit_rotated].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Ccw90,
+    Ccw180,
+    Ccw270,
+}
+
+impl<C> Framebuffer<'_, C>
+where
+    C: PixelColor + ToBytes,
+    C::Bytes: AsRef<[u8]>,
+{
+    /// Shifts the contents by `dx` pixels right and `dy` pixels down.
+    ///
+    /// Negative values shift left and up. Pixels shifted out are lost, and
+    /// the exposed strips are filled with `fill_color`. The pixel data is
+    /// moved a row at a time, so this is much faster than redrawing, e.g. for
+    /// terminals and scrolling plots. The whole framebuffer is marked dirty.
+    pub fn scroll(&mut self, dx: i32, dy: i32, fill_color: C) {
+        let size = self.size();
+        let (width, height) = (size.width as i32, size.height as i32);
+        if dx.abs() >= width || dy.abs() >= height {
+            let _ = self.fill_solid(&self.bounding_box(), fill_color);
+            return;
+        }
+
+        let bpp = self.bit_depth().bytes();
+        let row_len = width as usize * bpp;
+        let copy_len = (width - dx.abs()) as usize * bpp;
+        let src_x = (-dx).max(0) as usize * bpp;
+        let dst_x = dx.max(0) as usize * bpp;
+        let rows =
+            (0..height - dy.abs()).map(|i| if dy > 0 { height - dy - 1 - i } else { i - dy });
+        let data = self.data_mut();
+        for src_y in rows {
+            let src = src_y as usize * row_len + src_x;
+            let dst = (src_y + dy) as usize * row_len + dst_x;
+            data.copy_within(src..src + copy_len, dst);
+        }
+
+        let rows_exposed = if dy > 0 { 0..dy } else { height + dy..height };
+        let cols_exposed = if dx > 0 { 0..dx } else { width + dx..width };
+        let _ = self.fill_solid(
+            &Rectangle::new(
+                Point::new(0, rows_exposed.start),
+                Size::new(size.width, rows_exposed.len() as u32),
+            ),
+            fill_color,
+        );
+        let _ = self.fill_solid(
+            &Rectangle::new(
+                Point::new(cols_exposed.start, 0),
+                Size::new(cols_exposed.len() as u32, size.height),
+            ),
+            fill_color,
+        );
+        self.mark_dirty(self.bounding_box());
+    }
+
+    /// Copies the `src_rect` area of another framebuffer to `dst_point`.
+    ///
+    /// The copied area is clipped to both framebuffers. The pixel data is
+    /// copied a row at a time, e.g. to compose a screen from pre-rendered
+    /// tiles.
+    pub fn blit_from(&mut self, other: &Framebuffer<'_, C>, src_rect: Rectangle, dst_point: Point) {
+        self.blit_rows(other, src_rect, dst_point, |dst, src| {
+            dst.copy_from_slice(src)
+        });
+    }
+
+    /// Same as [Self::blit_from], but skips the source pixels of the
+    /// `transparent` color, e.g. magenta for sprites with irregular shapes.
+    pub fn blit_keyed(
+        &mut self,
+        other: &Framebuffer<'_, C>,
+        src_rect: Rectangle,
+        dst_point: Point,
+        transparent: C,
+    ) {
+        let key = transparent.to_be_bytes();
+        let key = key.as_ref();
+        let bpp = key.len();
+        self.blit_rows(other, src_rect, dst_point, |dst, src| {
+            for (d, s) in dst.chunks_mut(bpp).zip(src.chunks(bpp)) {
+                if s != key {
+                    d.copy_from_slice(s);
+                }
+            }
+        });
+    }
+
+    /// Fills the area by tiling another framebuffer across it.
+    ///
+    /// The tiles are aligned to the origin of this framebuffer, so adjacent
+    /// areas filled with the same pattern join seamlessly. Useful for
+    /// dithered backgrounds and textured panels that would be wasteful to
+    /// store full size.
+    pub fn fill_pattern(&mut self, area: Rectangle, pattern: &Framebuffer<'_, C>) {
+        let area = area.intersection(&self.bounding_box());
+        let tile = pattern.size();
+        if area.is_zero_sized() || tile.width == 0 || tile.height == 0 {
+            return;
+        }
+        let bpp = self.bit_depth().bytes();
+        let (tile_width, width) = (tile.width as usize, self.size().width as usize);
+        let (x0, y0) = (area.top_left.x as usize, area.top_left.y as usize);
+        let src = pattern.data();
+        let dst = self.data_mut();
+        for y in y0..y0 + area.size.height as usize {
+            let src_row = &src[(y % tile.height as usize) * tile_width * bpp..][..tile_width * bpp];
+            let mut x = x0;
+            while x < x0 + area.size.width as usize {
+                let tx = x % tile_width;
+                let n = (tile_width - tx).min(x0 + area.size.width as usize - x);
+                let d = (y * width + x) * bpp;
+                dst[d..d + n * bpp].copy_from_slice(&src_row[tx * bpp..(tx + n) * bpp]);
+                x += n;
+            }
+        }
+        self.mark_dirty(area);
+    }
+
+    /// Copies another framebuffer rotated counter-clockwise, with the
+    /// top-left corner of the rotated image at `dst_point`.
+    ///
+    /// Useful to reorient assets authored for a different [`Config`]
+    /// orientation. The copied area is clipped to this framebuffer.
+    ///
+    /// [`Config`]: crate::Config
+    pub fn blit_rotated(
+        &mut self,
+        other: &Framebuffer<'_, C>,
+        dst_point: Point,
+        rotation: Rotation,
+    ) {
+        let src_size = other.size();
+        let (w, h) = (src_size.width as i32, src_size.height as i32);
+        let size = match rotation {
+            Rotation::Ccw180 => src_size,
+            Rotation::Ccw90 | Rotation::Ccw270 => Size::new(src_size.height, src_size.width),
+        };
+        let area = Rectangle::new(dst_point, size).intersection(&self.bounding_box());
+        if area.is_zero_sized() {
+            return;
+        }
+        let bpp = self.bit_depth().bytes();
+        let width = self.size().width as usize;
+        let src = other.data();
+        let dst = self.data_mut();
+        for p in area.points() {
+            // Position within the rotated image, and the source pixel for it.
+            let Point { x, y } = p - dst_point;
+            let (sx, sy) = match rotation {
+                Rotation::Ccw90 => (w - 1 - y, x),
+                Rotation::Ccw180 => (w - 1 - x, h - 1 - y),
+                Rotation::Ccw270 => (y, h - 1 - x),
+            };
+            let s = (sy * w + sx) as usize * bpp;
+            let d = (p.y as usize * width + p.x as usize) * bpp;
+            dst[d..d + bpp].copy_from_slice(&src[s..s + bpp]);
+        }
+        self.mark_dirty(area);
+    }
+
+    /// Mirrors the area left to right, in place.
+    ///
+    /// The area is clipped to the framebuffer. Together with
+    /// [Self::flip_vertical], lets a single sprite face either way.
+    pub fn flip_horizontal(&mut self, area: Rectangle) {
+        let area = area.intersection(&self.bounding_box());
+        if area.is_zero_sized() {
+            return;
+        }
+        let bpp = self.bit_depth().bytes();
+        let width = self.size().width as usize;
+        let (x0, y0) = (area.top_left.x as usize, area.top_left.y as usize);
+        let n = area.size.width as usize;
+        let data = self.data_mut();
+        for y in y0..y0 + area.size.height as usize {
+            let row = &mut data[(y * width + x0) * bpp..][..n * bpp];
+            for i in 0..n / 2 {
+                for b in 0..bpp {
+                    row.swap(i * bpp + b, (n - 1 - i) * bpp + b);
+                }
+            }
+        }
+        self.mark_dirty(area);
+    }
+
+    /// Mirrors the area top to bottom, in place.
+    ///
+    /// The area is clipped to the framebuffer.
+    pub fn flip_vertical(&mut self, area: Rectangle) {
+        let area = area.intersection(&self.bounding_box());
+        if area.is_zero_sized() {
+            return;
+        }
+        let bpp = self.bit_depth().bytes();
+        let row_len = self.size().width as usize * bpp;
+        let (x, y0) = (area.top_left.x as usize * bpp, area.top_left.y as usize);
+        let (n, len) = (area.size.height as usize, area.size.width as usize * bpp);
+        let data = self.data_mut();
+        for i in 0..n / 2 {
+            let (top, bottom) = data.split_at_mut((y0 + n - 1 - i) * row_len);
+            let top = &mut top[(y0 + i) * row_len + x..][..len];
+            top.swap_with_slice(&mut bottom[x..x + len]);
+        }
+        self.mark_dirty(area);
+    }
+
+    // Calls `f` with each pair of destination and source rows of the
+    // clipped blit area, and marks the area dirty.
+    fn blit_rows(
+        &mut self,
+        other: &Framebuffer<'_, C>,
+        src_rect: Rectangle,
+        dst_point: Point,
+        mut f: impl FnMut(&mut [u8], &[u8]),
+    ) {
+        let src = src_rect.intersection(&other.bounding_box());
+        let dst = Rectangle::new(dst_point + (src.top_left - src_rect.top_left), src.size);
+        let clipped = dst.intersection(&self.bounding_box());
+        if clipped.is_zero_sized() {
+            return;
+        }
+        let src_tl = src.top_left + (clipped.top_left - dst.top_left);
+
+        let bpp = self.bit_depth().bytes();
+        let len = clipped.size.width as usize * bpp;
+        let offset = |p: Point, width: u32| (p.y as usize * width as usize + p.x as usize) * bpp;
+        let (src_width, dst_width) = (other.size().width, self.size().width);
+        let src_data = other.data();
+        let dst_data = self.data_mut();
+        for y in 0..clipped.size.height as i32 {
+            let s = offset(src_tl + Point::new(0, y), src_width);
+            let d = offset(clipped.top_left + Point::new(0, y), dst_width);
+            f(&mut dst_data[d..d + len], &src_data[s..s + len]);
+        }
+        self.mark_dirty(clipped);
+    }
+}
+
+impl Framebuffer<'_, Rgb565> {
+    /// Same as [Self::blit_from], but blends the source pixels over the
+    /// existing ones with the given opacity, e.g. for translucent overlays.
+    pub fn blit_blended(
+        &mut self,
+        other: &Framebuffer<'_, Rgb565>,
+        src_rect: Rectangle,
+        dst_point: Point,
+        opacity: Opacity,
+    ) {
+        self.blit_rows(other, src_rect, dst_point, |dst, src| {
+            for (d, s) in dst.chunks_mut(2).zip(src.chunks(2)) {
+                let a = u16::from_be_bytes([d[0], d[1]]);
+                let b = u16::from_be_bytes([s[0], s[1]]);
+                d.copy_from_slice(&blend_raw(a, b, opacity).to_be_bytes());
+            }
+        });
+    }
+}
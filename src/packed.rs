@@ -0,0 +1,189 @@
+use embedded_graphics_core::{
+    image::GetPixel,
+    pixelcolor::{
+        raw::{RawData, ToBytes},
+        BinaryColor,
+    },
+    prelude::{DrawTarget, OriginDimensions, PixelColor, Point, Size},
+    primitives::Rectangle,
+    Pixel,
+};
+
+use crate::{expand::write_expanded, geometry::union, BitDepth, TryWritePixels};
+
+/// Framebuffer with pixels of up to 8 bits packed into bytes.
+///
+/// Stores the raw data of colors with 1, 2, 4 or 8 bits per pixel, such as
+/// [`BinaryColor`], most significant bits first, with each row starting on a
+/// byte boundary. The display doesn't support these formats, so the pixels
+/// are expanded through a palette when sent with [`flush_packed`]. Saves a
+/// lot of RAM for UIs that only need a few colors; e.g. a full-screen
+/// [`MonoFramebuffer`] takes 768 bytes instead of 12KB.
+///
+/// Like [`Framebuffer`](crate::Framebuffer), keeps track of the dirty area.
+pub struct PackedFramebuffer<'a, C> {
+    size: Size,
+    data: &'a mut [u8],
+    dirty: Rectangle,
+    _color: core::marker::PhantomData<C>,
+}
+
+/// Framebuffer with one bit per pixel.
+pub type MonoFramebuffer<'a> = PackedFramebuffer<'a, BinaryColor>;
+
+impl<'a, C> PackedFramebuffer<'a, C>
+where
+    C: PixelColor + From<C::Raw>,
+    C::Raw: RawData<Storage = u8> + From<C>,
+{
+    const BITS: usize = C::Raw::BITS_PER_PIXEL;
+
+    /// Creates a framebuffer.
+    ///
+    /// Panics if the data slice is too small to hold the requested size, or
+    /// the color doesn't pack evenly into bytes.
+    pub fn new(data: &'a mut [u8], size: Size) -> Self {
+        assert!(8 % Self::BITS == 0);
+        assert!(data.len() >= Self::row_bytes(size.width) * size.height as usize);
+        Self {
+            size,
+            data,
+            dirty: Rectangle::new(Point::zero(), size),
+            _color: core::marker::PhantomData,
+        }
+    }
+
+    /// Returns the number of bytes needed for a row of the given width.
+    pub const fn row_bytes(width: u32) -> usize {
+        (width as usize * Self::BITS).div_ceil(8)
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data[..Self::row_bytes(self.size.width) * self.size.height as usize]
+    }
+
+    /// See [Framebuffer::take_dirty](crate::Framebuffer::take_dirty).
+    pub fn take_dirty(&mut self) -> Option<Rectangle> {
+        let dirty = core::mem::replace(&mut self.dirty, Rectangle::zero());
+        (!dirty.is_zero_sized()).then_some(dirty)
+    }
+
+    // Returns the palette index of the pixel, which must be within bounds.
+    fn index(&self, x: usize, y: usize) -> u8 {
+        let (byte, shift) = self.position(x, y);
+        (self.data[byte] >> shift) & Self::mask()
+    }
+
+    // Returns the byte offset and the bit shift of the pixel.
+    fn position(&self, x: usize, y: usize) -> (usize, usize) {
+        let bit = x * Self::BITS;
+        let byte = y * Self::row_bytes(self.size.width) + bit / 8;
+        (byte, 8 - Self::BITS - bit % 8)
+    }
+
+    fn mask() -> u8 {
+        (0xFF >> (8 - Self::BITS)) as u8
+    }
+}
+
+impl<C> OriginDimensions for PackedFramebuffer<'_, C> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl<C> DrawTarget for PackedFramebuffer<'_, C>
+where
+    C: PixelColor + From<C::Raw>,
+    C::Raw: RawData<Storage = u8> + From<C>,
+{
+    type Color = C;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let mut min = Point::new(i32::MAX, i32::MAX);
+        let mut max = Point::new(i32::MIN, i32::MIN);
+        for Pixel(p, color) in pixels {
+            let (Ok(x), Ok(y)) = (usize::try_from(p.x), usize::try_from(p.y)) else {
+                continue;
+            };
+            if x >= self.size.width as usize || y >= self.size.height as usize {
+                continue;
+            }
+            let (byte, shift) = self.position(x, y);
+            let index = C::Raw::from(color).into_inner() & Self::mask();
+            self.data[byte] = self.data[byte] & !(Self::mask() << shift) | index << shift;
+            min = min.component_min(p);
+            max = max.component_max(p);
+        }
+        if min.x <= max.x {
+            self.dirty = union(self.dirty, Rectangle::with_corners(min, max));
+        }
+        Ok(())
+    }
+}
+
+impl<C> GetPixel for PackedFramebuffer<'_, C>
+where
+    C: PixelColor + From<C::Raw>,
+    C::Raw: RawData<Storage = u8> + From<C>,
+{
+    type Color = C;
+
+    fn pixel(&self, p: Point) -> Option<C> {
+        let (Ok(x), Ok(y)) = (usize::try_from(p.x), usize::try_from(p.y)) else {
+            return None;
+        };
+        if x >= self.size.width as usize || y >= self.size.height as usize {
+            return None;
+        }
+        Some(C::Raw::from_u32(self.index(x, y).into()).into())
+    }
+}
+
+/// Sends the dirty area of a packed framebuffer to the display, expanding
+/// the pixels through a palette.
+///
+/// Each pixel is sent as the palette entry at the index given by its raw
+/// value; e.g. `[background, foreground]` for a [`MonoFramebuffer`]. The
+/// expanded pixels are written into `buf` as many whole rows at a time as
+/// it can hold. The framebuffer's top-left corner is placed at `top_left`
+/// on the display, and the framebuffer is marked clean afterwards.
+///
+/// # Panics
+///
+/// If the palette has fewer entries than the color has values, or `buf`
+/// can't hold an expanded row of the dirty area.
+pub async fn flush_packed<D, C, P>(
+    display: &mut D,
+    fb: &mut PackedFramebuffer<'_, C>,
+    top_left: Point,
+    palette: &[P],
+    buf: &mut [u8],
+) -> Result<(), D::Error>
+where
+    D: TryWritePixels + ?Sized,
+    C: PixelColor + From<C::Raw>,
+    C::Raw: RawData<Storage = u8> + From<C>,
+    P: PixelColor + ToBytes,
+    P::Bytes: AsRef<[u8]>,
+{
+    assert!(palette.len() >= 1 << PackedFramebuffer::<C>::BITS);
+    let Some(dirty) = fb.take_dirty() else {
+        return Ok(());
+    };
+    let bit_depth = BitDepth::of::<P>();
+    let bpp = bit_depth.bytes();
+    let area = Rectangle::new(top_left + dirty.top_left, dirty.size);
+    let (x0, y0) = (dirty.top_left.x as usize, dirty.top_left.y as usize);
+    write_expanded(display, area, bit_depth, buf, |y, row| {
+        for (x, dst) in row.chunks_mut(bpp).enumerate() {
+            let color = palette[fb.index(x0 + x, y0 + y as usize) as usize];
+            dst.copy_from_slice(color.to_be_bytes().as_ref());
+        }
+    })
+    .await
+}
@@ -0,0 +1,73 @@
+use embedded_graphics_core::{
+    pixelcolor::{Rgb565, Rgb888},
+    prelude::RgbColor,
+};
+
+use crate::Rgb332;
+
+/// A set of colors, for indexed framebuffers and image quantization.
+///
+/// Pixels drawn as indices into the palette, e.g. in an
+/// [`IndexedFramebuffer`](crate::IndexedFramebuffer), are sent with
+/// [`flush_packed`](crate::flush_packed) and the palette's colors in the
+/// display format (see [Self::colors_as]). [Self::nearest] picks the index
+/// for an arbitrary color, e.g. when converting images.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Palette<const N: usize> {
+    colors: [Rgb888; N],
+}
+
+impl<const N: usize> Palette<N> {
+    pub const fn new(colors: [Rgb888; N]) -> Self {
+        Self { colors }
+    }
+
+    pub const fn colors(&self) -> &[Rgb888; N] {
+        &self.colors
+    }
+
+    /// Returns the colors converted to another format, e.g. [`Rgb565`] for
+    /// [`flush_packed`](crate::flush_packed).
+    pub fn colors_as<C: From<Rgb888>>(&self) -> [C; N] {
+        core::array::from_fn(|i| self.colors[i].into())
+    }
+
+    /// Returns the index of the color closest to `color`, or the first of
+    /// several equally close ones.
+    ///
+    /// The distance weighs the channels by how sensitive the eye is to
+    /// them.
+    ///
+    /// # Panics
+    ///
+    /// If the palette is empty.
+    pub fn nearest(&self, color: Rgb888) -> usize {
+        let distance = |c: &Rgb888| {
+            let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+            3 * d(c.r(), color.r()) + 4 * d(c.g(), color.g()) + 2 * d(c.b(), color.b())
+        };
+        (0..N).min_by_key(|&i| distance(&self.colors[i])).unwrap()
+    }
+
+    /// Same as [Self::nearest], for an [`Rgb565`] color.
+    pub fn nearest_rgb565(&self, color: Rgb565) -> usize {
+        self.nearest(color.into())
+    }
+
+    /// Returns the index of the nearest color for each of the 256 [`Rgb332`]
+    /// colors, indexed by the raw value.
+    ///
+    /// Looking up a precomputed table is much faster than [Self::nearest],
+    /// e.g. to convert an 8-bit framebuffer or image to palette indices.
+    ///
+    /// # Panics
+    ///
+    /// If the palette is empty or has more than 256 colors.
+    pub fn rgb332_table(&self) -> [u8; 256] {
+        assert!(N <= 256);
+        core::array::from_fn(|i| {
+            let c = Rgb332::new((i >> 5) as u8, (i >> 2) as u8 & 7, i as u8 & 3);
+            self.nearest_rgb565(c.into()) as u8
+        })
+    }
+}
@@ -0,0 +1,116 @@
+use core::convert::Infallible;
+
+use embedded_hal::digital::OutputPin;
+
+use crate::{DataCommand, Error};
+
+/// Eight data lines of a parallel bus, D0 to D7.
+///
+/// Implemented for an array of eight GPIO pins, with D0 first. For speed,
+/// implement it for a GPIO port instead, writing all the lines with a
+/// single register access.
+pub trait DataBus8 {
+    type Error;
+
+    /// Sets the data lines to the bits of the value.
+    fn write(&mut self, value: u8) -> Result<(), Self::Error>;
+}
+
+impl<P: OutputPin> DataBus8 for [P; 8] {
+    type Error = P::Error;
+
+    fn write(&mut self, value: u8) -> Result<(), Self::Error> {
+        for (bit, pin) in self.iter_mut().enumerate() {
+            pin.set_state((value >> bit & 1 == 1).into())?;
+        }
+        Ok(())
+    }
+}
+
+/// Which of the controller's parallel protocols the bus is wired for, set
+/// by its BS pins.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParallelMode {
+    /// 8080 series: the strobe is the active-low WR pin, and the byte is
+    /// latched on its rising edge.
+    I8080,
+    /// 6800 series: the strobe is the active-high E pin, and the byte is
+    /// latched on its falling edge. The R/W pin needs to be tied low.
+    M6800,
+}
+
+/// Interface over an 8-bit parallel bus, driven by GPIO.
+///
+/// Uses a D/C pin like [`SpiInterface`](crate::SpiInterface), a strobe pin
+/// and the [`DataBus8`] lines; CS needs to be tied low, and RD (or R/W)
+/// inactive. Each byte is written by setting the data lines and pulsing the
+/// strobe, with no delays, as GPIO writes on most MCUs are slower than the
+/// controller's write cycle anyway. All errors are reported as
+/// [`Error::Pin`].
+pub struct ParallelInterface<DC, WR, B> {
+    dc: DC,
+    strobe: WR,
+    bus: B,
+    mode: ParallelMode,
+}
+
+impl<DC, WR, B, E> ParallelInterface<DC, WR, B>
+where
+    DC: OutputPin<Error = E>,
+    WR: OutputPin<Error = E>,
+    B: DataBus8<Error = E>,
+{
+    /// Creates the interface, setting the strobe to its idle level.
+    pub fn new(dc: DC, mut strobe: WR, bus: B, mode: ParallelMode) -> Result<Self, E> {
+        match mode {
+            ParallelMode::I8080 => strobe.set_high()?,
+            ParallelMode::M6800 => strobe.set_low()?,
+        }
+        Ok(Self {
+            dc,
+            strobe,
+            bus,
+            mode,
+        })
+    }
+
+    /// Returns the pins and the bus.
+    pub fn release(self) -> (DC, WR, B) {
+        (self.dc, self.strobe, self.bus)
+    }
+
+    fn write(&mut self, data: bool, bytes: &[u8]) -> Result<(), E> {
+        self.dc.set_state(data.into())?;
+        for &byte in bytes {
+            self.bus.write(byte)?;
+            match self.mode {
+                ParallelMode::I8080 => {
+                    self.strobe.set_low()?;
+                    self.strobe.set_high()?;
+                }
+                ParallelMode::M6800 => {
+                    self.strobe.set_high()?;
+                    self.strobe.set_low()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<DC, WR, B, E> DataCommand for ParallelInterface<DC, WR, B>
+where
+    DC: OutputPin<Error = E>,
+    WR: OutputPin<Error = E>,
+    B: DataBus8<Error = E>,
+{
+    type Error = Error<E, Infallible>;
+
+    async fn send_commands(&mut self, commands: &[u8]) -> Result<(), Self::Error> {
+        self.write(false, commands).map_err(Error::Pin)
+    }
+
+    async fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.write(true, data).map_err(Error::Pin)
+    }
+}
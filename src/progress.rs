@@ -0,0 +1,98 @@
+use embedded_graphics_core::{
+    pixelcolor::Rgb565,
+    prelude::{Point, Size},
+    primitives::Rectangle,
+};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{DataCommand, Error, Ssd1331};
+
+/// Horizontal progress bar drawn with the display's rectangle command.
+///
+/// [Self::draw_frame] draws the border and the empty interior, and
+/// [Self::set] then fills or clears only the part of the interior that
+/// changed. Each update is a single rectangle command of about a dozen
+/// bytes, so boot or update screens don't need a framebuffer.
+pub struct ProgressBar {
+    area: Rectangle,
+    border: Rgb565,
+    fill: Rgb565,
+    background: Rgb565,
+    // Width of the filled part of the interior, in pixels.
+    filled: u32,
+}
+
+impl ProgressBar {
+    /// Creates a progress bar in the area of the display, including a
+    /// one-pixel border. Nothing is drawn until [Self::draw_frame].
+    pub const fn new(area: Rectangle, border: Rgb565, fill: Rgb565, background: Rgb565) -> Self {
+        Self {
+            area,
+            border,
+            fill,
+            background,
+            filled: 0,
+        }
+    }
+
+    /// Draws the border with an empty interior.
+    pub async fn draw_frame<RST, DI, PinE, SpiE>(
+        &mut self,
+        display: &mut Ssd1331<RST, DI>,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), Error<PinE, SpiE>>
+    where
+        RST: OutputPin<Error = PinE>,
+        DI: DataCommand<Error = Error<PinE, SpiE>>,
+    {
+        display
+            .draw_rectangle(self.area, self.border, Some(self.background), delay)
+            .await?;
+        self.filled = 0;
+        Ok(())
+    }
+
+    /// Shows `value` out of `max` as filled, clamping to `max`.
+    ///
+    /// Only the columns that changed since the last call are drawn. Assumes
+    /// the frame has been drawn.
+    pub async fn set<RST, DI, PinE, SpiE>(
+        &mut self,
+        display: &mut Ssd1331<RST, DI>,
+        value: u32,
+        max: u32,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), Error<PinE, SpiE>>
+    where
+        RST: OutputPin<Error = PinE>,
+        DI: DataCommand<Error = Error<PinE, SpiE>>,
+    {
+        let interior = Rectangle::new(
+            self.area.top_left + Point::new(1, 1),
+            self.area.size.saturating_sub(Size::new(2, 2)),
+        );
+        let filled = match max {
+            0 => interior.size.width,
+            _ => {
+                (u64::from(value.min(max)) * u64::from(interior.size.width) / u64::from(max)) as u32
+            }
+        };
+        let (from, to, color) = if filled > self.filled {
+            (self.filled, filled, self.fill)
+        } else {
+            (filled, self.filled, self.background)
+        };
+        if from < to && interior.size.height > 0 {
+            let change = Rectangle::new(
+                interior.top_left + Point::new(from as i32, 0),
+                Size::new(to - from, interior.size.height),
+            );
+            display
+                .draw_rectangle(change, color, Some(color), delay)
+                .await?;
+        }
+        self.filled = filled;
+        Ok(())
+    }
+}
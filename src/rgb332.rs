@@ -1,8 +1,14 @@
 use embedded_graphics_core::{
-    pixelcolor::{raw::RawU8, Gray8, Rgb888},
-    prelude::{GrayColor, IntoStorage, PixelColor, RgbColor},
+    pixelcolor::{
+        raw::{RawU16, RawU8},
+        Gray8, Rgb565, Rgb888,
+    },
+    prelude::{GrayColor, IntoStorage, OriginDimensions, PixelColor, Point, RgbColor, WebColors},
+    primitives::Rectangle,
 };
 
+use crate::{expand::write_expanded, BitDepth, Framebuffer, TryAdapter, WritePixels};
+
 /// Color format used by SSD1331 display when in 8-bit color mode.
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Rgb332(RawU8);
@@ -13,6 +19,15 @@ impl Rgb332 {
             ((r & Self::MAX_R) << 5) | ((g & Self::MAX_G) << 2) | (b & Self::MAX_B),
         ))
     }
+
+    // Converts the channels of a 24-bit color, rounding to the nearest
+    // value.
+    const fn with_rgb888(r: u8, g: u8, b: u8) -> Self {
+        const fn scale(value: u8, max: u8) -> u8 {
+            ((value as u16 * max as u16 + 127) / 255) as u8
+        }
+        Self::new(scale(r, 7), scale(g, 7), scale(b, 3))
+    }
 }
 
 impl PixelColor for Rgb332 {
@@ -58,6 +73,155 @@ impl RgbColor for Rgb332 {
     const WHITE: Self = Self::new(Self::MAX_R, Self::MAX_G, Self::MAX_B);
 }
 
+/// Named web colors, for code shared with 16-bit mode.
+///
+/// Each color is rounded to the nearest 3-3-2 value, so some of them
+/// coincide.
+#[rustfmt::skip]
+impl WebColors for Rgb332 {
+    const CSS_ALICE_BLUE: Self = Self::with_rgb888(240, 248, 255);
+    const CSS_ANTIQUE_WHITE: Self = Self::with_rgb888(250, 235, 215);
+    const CSS_AQUA: Self = Self::with_rgb888(0, 255, 255);
+    const CSS_AQUAMARINE: Self = Self::with_rgb888(127, 255, 212);
+    const CSS_AZURE: Self = Self::with_rgb888(240, 255, 255);
+    const CSS_BEIGE: Self = Self::with_rgb888(245, 245, 220);
+    const CSS_BISQUE: Self = Self::with_rgb888(255, 228, 196);
+    const CSS_BLACK: Self = Self::with_rgb888(0, 0, 0);
+    const CSS_BLANCHED_ALMOND: Self = Self::with_rgb888(255, 235, 205);
+    const CSS_BLUE: Self = Self::with_rgb888(0, 0, 255);
+    const CSS_BLUE_VIOLET: Self = Self::with_rgb888(138, 43, 226);
+    const CSS_BROWN: Self = Self::with_rgb888(165, 42, 42);
+    const CSS_BURLY_WOOD: Self = Self::with_rgb888(222, 184, 135);
+    const CSS_CADET_BLUE: Self = Self::with_rgb888(95, 158, 160);
+    const CSS_CHARTREUSE: Self = Self::with_rgb888(127, 255, 0);
+    const CSS_CHOCOLATE: Self = Self::with_rgb888(210, 105, 30);
+    const CSS_CORAL: Self = Self::with_rgb888(255, 127, 80);
+    const CSS_CORNFLOWER_BLUE: Self = Self::with_rgb888(100, 149, 237);
+    const CSS_CORNSILK: Self = Self::with_rgb888(255, 248, 220);
+    const CSS_CRIMSON: Self = Self::with_rgb888(220, 20, 60);
+    const CSS_CYAN: Self = Self::with_rgb888(0, 255, 255);
+    const CSS_DARK_BLUE: Self = Self::with_rgb888(0, 0, 139);
+    const CSS_DARK_CYAN: Self = Self::with_rgb888(0, 139, 139);
+    const CSS_DARK_GOLDENROD: Self = Self::with_rgb888(184, 134, 11);
+    const CSS_DARK_GRAY: Self = Self::with_rgb888(169, 169, 169);
+    const CSS_DARK_GREEN: Self = Self::with_rgb888(0, 100, 0);
+    const CSS_DARK_KHAKI: Self = Self::with_rgb888(189, 183, 107);
+    const CSS_DARK_MAGENTA: Self = Self::with_rgb888(139, 0, 139);
+    const CSS_DARK_OLIVE_GREEN: Self = Self::with_rgb888(85, 107, 47);
+    const CSS_DARK_ORANGE: Self = Self::with_rgb888(255, 140, 0);
+    const CSS_DARK_ORCHID: Self = Self::with_rgb888(153, 50, 204);
+    const CSS_DARK_RED: Self = Self::with_rgb888(139, 0, 0);
+    const CSS_DARK_SALMON: Self = Self::with_rgb888(233, 150, 122);
+    const CSS_DARK_SEA_GREEN: Self = Self::with_rgb888(143, 188, 143);
+    const CSS_DARK_SLATE_BLUE: Self = Self::with_rgb888(72, 61, 139);
+    const CSS_DARK_SLATE_GRAY: Self = Self::with_rgb888(47, 79, 79);
+    const CSS_DARK_TURQUOISE: Self = Self::with_rgb888(0, 206, 209);
+    const CSS_DARK_VIOLET: Self = Self::with_rgb888(148, 0, 211);
+    const CSS_DEEP_PINK: Self = Self::with_rgb888(255, 20, 147);
+    const CSS_DEEP_SKY_BLUE: Self = Self::with_rgb888(0, 191, 255);
+    const CSS_DIM_GRAY: Self = Self::with_rgb888(105, 105, 105);
+    const CSS_DODGER_BLUE: Self = Self::with_rgb888(30, 144, 255);
+    const CSS_FIRE_BRICK: Self = Self::with_rgb888(178, 34, 34);
+    const CSS_FLORAL_WHITE: Self = Self::with_rgb888(255, 250, 240);
+    const CSS_FOREST_GREEN: Self = Self::with_rgb888(34, 139, 34);
+    const CSS_FUCHSIA: Self = Self::with_rgb888(255, 0, 255);
+    const CSS_GAINSBORO: Self = Self::with_rgb888(220, 220, 220);
+    const CSS_GHOST_WHITE: Self = Self::with_rgb888(248, 248, 255);
+    const CSS_GOLD: Self = Self::with_rgb888(255, 215, 0);
+    const CSS_GOLDENROD: Self = Self::with_rgb888(218, 165, 32);
+    const CSS_GRAY: Self = Self::with_rgb888(128, 128, 128);
+    const CSS_GREEN: Self = Self::with_rgb888(0, 128, 0);
+    const CSS_GREEN_YELLOW: Self = Self::with_rgb888(173, 255, 47);
+    const CSS_HONEYDEW: Self = Self::with_rgb888(240, 255, 240);
+    const CSS_HOT_PINK: Self = Self::with_rgb888(255, 105, 180);
+    const CSS_INDIAN_RED: Self = Self::with_rgb888(205, 92, 92);
+    const CSS_INDIGO: Self = Self::with_rgb888(75, 0, 130);
+    const CSS_IVORY: Self = Self::with_rgb888(255, 255, 240);
+    const CSS_KHAKI: Self = Self::with_rgb888(240, 230, 140);
+    const CSS_LAVENDER: Self = Self::with_rgb888(230, 230, 250);
+    const CSS_LAVENDER_BLUSH: Self = Self::with_rgb888(255, 240, 245);
+    const CSS_LAWN_GREEN: Self = Self::with_rgb888(124, 252, 0);
+    const CSS_LEMON_CHIFFON: Self = Self::with_rgb888(255, 250, 205);
+    const CSS_LIGHT_BLUE: Self = Self::with_rgb888(173, 216, 230);
+    const CSS_LIGHT_CORAL: Self = Self::with_rgb888(240, 128, 128);
+    const CSS_LIGHT_CYAN: Self = Self::with_rgb888(224, 255, 255);
+    const CSS_LIGHT_GOLDENROD_YELLOW: Self = Self::with_rgb888(250, 250, 210);
+    const CSS_LIGHT_GRAY: Self = Self::with_rgb888(211, 211, 211);
+    const CSS_LIGHT_GREEN: Self = Self::with_rgb888(144, 238, 144);
+    const CSS_LIGHT_PINK: Self = Self::with_rgb888(255, 182, 193);
+    const CSS_LIGHT_SALMON: Self = Self::with_rgb888(255, 160, 122);
+    const CSS_LIGHT_SEA_GREEN: Self = Self::with_rgb888(32, 178, 170);
+    const CSS_LIGHT_SKY_BLUE: Self = Self::with_rgb888(135, 206, 250);
+    const CSS_LIGHT_SLATE_GRAY: Self = Self::with_rgb888(119, 136, 153);
+    const CSS_LIGHT_STEEL_BLUE: Self = Self::with_rgb888(176, 196, 222);
+    const CSS_LIGHT_YELLOW: Self = Self::with_rgb888(255, 255, 224);
+    const CSS_LIME: Self = Self::with_rgb888(0, 255, 0);
+    const CSS_LIME_GREEN: Self = Self::with_rgb888(50, 205, 50);
+    const CSS_LINEN: Self = Self::with_rgb888(250, 240, 230);
+    const CSS_MAGENTA: Self = Self::with_rgb888(255, 0, 255);
+    const CSS_MAROON: Self = Self::with_rgb888(128, 0, 0);
+    const CSS_MEDIUM_AQUAMARINE: Self = Self::with_rgb888(102, 205, 170);
+    const CSS_MEDIUM_BLUE: Self = Self::with_rgb888(0, 0, 205);
+    const CSS_MEDIUM_ORCHID: Self = Self::with_rgb888(186, 85, 211);
+    const CSS_MEDIUM_PURPLE: Self = Self::with_rgb888(147, 112, 219);
+    const CSS_MEDIUM_SEA_GREEN: Self = Self::with_rgb888(60, 179, 113);
+    const CSS_MEDIUM_SLATE_BLUE: Self = Self::with_rgb888(123, 104, 238);
+    const CSS_MEDIUM_SPRING_GREEN: Self = Self::with_rgb888(0, 250, 154);
+    const CSS_MEDIUM_TURQUOISE: Self = Self::with_rgb888(72, 209, 204);
+    const CSS_MEDIUM_VIOLET_RED: Self = Self::with_rgb888(199, 21, 133);
+    const CSS_MIDNIGHT_BLUE: Self = Self::with_rgb888(25, 25, 112);
+    const CSS_MINT_CREAM: Self = Self::with_rgb888(245, 255, 250);
+    const CSS_MISTY_ROSE: Self = Self::with_rgb888(255, 228, 225);
+    const CSS_MOCCASIN: Self = Self::with_rgb888(255, 228, 181);
+    const CSS_NAVAJO_WHITE: Self = Self::with_rgb888(255, 222, 173);
+    const CSS_NAVY: Self = Self::with_rgb888(0, 0, 128);
+    const CSS_OLD_LACE: Self = Self::with_rgb888(253, 245, 230);
+    const CSS_OLIVE: Self = Self::with_rgb888(128, 128, 0);
+    const CSS_OLIVE_DRAB: Self = Self::with_rgb888(107, 142, 35);
+    const CSS_ORANGE: Self = Self::with_rgb888(255, 165, 0);
+    const CSS_ORANGE_RED: Self = Self::with_rgb888(255, 69, 0);
+    const CSS_ORCHID: Self = Self::with_rgb888(218, 112, 214);
+    const CSS_PALE_GOLDENROD: Self = Self::with_rgb888(238, 232, 170);
+    const CSS_PALE_GREEN: Self = Self::with_rgb888(152, 251, 152);
+    const CSS_PALE_TURQUOISE: Self = Self::with_rgb888(175, 238, 238);
+    const CSS_PALE_VIOLET_RED: Self = Self::with_rgb888(219, 112, 147);
+    const CSS_PAPAYA_WHIP: Self = Self::with_rgb888(255, 239, 213);
+    const CSS_PEACH_PUFF: Self = Self::with_rgb888(255, 218, 185);
+    const CSS_PERU: Self = Self::with_rgb888(205, 133, 63);
+    const CSS_PINK: Self = Self::with_rgb888(255, 192, 203);
+    const CSS_PLUM: Self = Self::with_rgb888(221, 160, 221);
+    const CSS_POWDER_BLUE: Self = Self::with_rgb888(176, 224, 230);
+    const CSS_PURPLE: Self = Self::with_rgb888(128, 0, 128);
+    const CSS_REBECCAPURPLE: Self = Self::with_rgb888(102, 51, 153);
+    const CSS_RED: Self = Self::with_rgb888(255, 0, 0);
+    const CSS_ROSY_BROWN: Self = Self::with_rgb888(188, 143, 143);
+    const CSS_ROYAL_BLUE: Self = Self::with_rgb888(65, 105, 225);
+    const CSS_SADDLE_BROWN: Self = Self::with_rgb888(139, 69, 19);
+    const CSS_SALMON: Self = Self::with_rgb888(250, 128, 114);
+    const CSS_SANDY_BROWN: Self = Self::with_rgb888(244, 164, 96);
+    const CSS_SEA_GREEN: Self = Self::with_rgb888(46, 139, 87);
+    const CSS_SEASHELL: Self = Self::with_rgb888(255, 245, 238);
+    const CSS_SIENNA: Self = Self::with_rgb888(160, 82, 45);
+    const CSS_SILVER: Self = Self::with_rgb888(192, 192, 192);
+    const CSS_SKY_BLUE: Self = Self::with_rgb888(135, 206, 235);
+    const CSS_SLATE_BLUE: Self = Self::with_rgb888(106, 90, 205);
+    const CSS_SLATE_GRAY: Self = Self::with_rgb888(112, 128, 144);
+    const CSS_SNOW: Self = Self::with_rgb888(255, 250, 250);
+    const CSS_SPRING_GREEN: Self = Self::with_rgb888(0, 255, 127);
+    const CSS_STEEL_BLUE: Self = Self::with_rgb888(70, 130, 180);
+    const CSS_TAN: Self = Self::with_rgb888(210, 180, 140);
+    const CSS_TEAL: Self = Self::with_rgb888(0, 128, 128);
+    const CSS_THISTLE: Self = Self::with_rgb888(216, 191, 216);
+    const CSS_TOMATO: Self = Self::with_rgb888(255, 99, 71);
+    const CSS_TURQUOISE: Self = Self::with_rgb888(64, 224, 208);
+    const CSS_VIOLET: Self = Self::with_rgb888(238, 130, 238);
+    const CSS_WHEAT: Self = Self::with_rgb888(245, 222, 179);
+    const CSS_WHITE: Self = Self::with_rgb888(255, 255, 255);
+    const CSS_WHITE_SMOKE: Self = Self::with_rgb888(245, 245, 245);
+    const CSS_YELLOW: Self = Self::with_rgb888(255, 255, 0);
+    const CSS_YELLOW_GREEN: Self = Self::with_rgb888(154, 205, 50);
+}
+
 impl From<Gray8> for Rgb332 {
     fn from(color: Gray8) -> Self {
         let luma = color.luma();
@@ -70,3 +234,73 @@ impl From<Rgb888> for Rgb332 {
         Self::new(c.r() >> 5, c.g() >> 5, c.b() >> 6)
     }
 }
+
+impl From<Rgb565> for Rgb332 {
+    fn from(c: Rgb565) -> Self {
+        Self::new(c.r() >> 2, c.g() >> 3, c.b() >> 3)
+    }
+}
+
+impl From<Rgb332> for Rgb565 {
+    fn from(c: Rgb332) -> Self {
+        RawU16::new(TO_RGB565[c.into_storage() as usize]).into()
+    }
+}
+
+// Rgb565 values for all Rgb332 colors. The channels are scaled by repeating
+// their bits, so that e.g. white stays white, unlike the display
+// controller's own conversion that fills the low bits with a fixed value.
+pub(crate) const TO_RGB565: [u16; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let (r, g, b) = ((i >> 5) as u16, ((i >> 2) & 7) as u16, (i & 3) as u16);
+        let r5 = (r << 2) | (r >> 1);
+        let g6 = (g << 3) | g;
+        let b5 = (b << 3) | (b << 1) | (b >> 1);
+        table[i] = (r5 << 11) | (g6 << 5) | b5;
+        i += 1;
+    }
+    table
+};
+
+/// Sends the dirty area of an 8-bit framebuffer to the display as 16-bit
+/// pixels.
+///
+/// Keeps the RAM use at one byte per pixel, while giving smoother gradients
+/// than sending the 8-bit data directly, at the cost of twice the transfer
+/// size. The converted pixels are written into `buf` as many whole rows at
+/// a time as it can hold. The framebuffer's top-left corner is placed at
+/// `top_left` on the display, and the framebuffer is marked clean
+/// afterwards.
+///
+/// # Panics
+///
+/// If `buf` can't hold a row of the dirty area in 16-bit color.
+pub async fn flush_rgb332_as_rgb565(
+    display: &mut (impl WritePixels + ?Sized),
+    fb: &mut Framebuffer<'_, Rgb332>,
+    top_left: Point,
+    buf: &mut [u8],
+) {
+    let Some(dirty) = fb.take_dirty() else {
+        return;
+    };
+    let area = Rectangle::new(top_left + dirty.top_left, dirty.size);
+    let (x0, y0) = (dirty.top_left.x as usize, dirty.top_left.y as usize);
+    let width = fb.size().width as usize;
+    let data = fb.data();
+    let Ok(()) = write_expanded(
+        &mut TryAdapter(display),
+        area,
+        BitDepth::Sixteen,
+        buf,
+        |y, dst| {
+            let src = &data[(y0 + y as usize) * width + x0..];
+            for (d, &s) in dst.chunks_mut(2).zip(src) {
+                d.copy_from_slice(&TO_RGB565[s as usize].to_be_bytes());
+            }
+        },
+    )
+    .await;
+}
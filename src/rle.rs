@@ -0,0 +1,162 @@
+//! Run-length encoded images.
+//!
+//! The format is a sequence of packets, each starting with a control byte
+//! `c`:
+//!
+//! - `c < 0x80`: `c + 1` literal pixels follow.
+//! - `c >= 0x80`: a single pixel follows, repeated `c - 0x80 + 1` times.
+//!
+//! Pixels are stored the same way as in [`Framebuffer`](crate::Framebuffer):
+//! one byte for 8-bit depth, or two big-endian bytes for 16-bit depth. There
+//! is no header; the image size and depth are passed to the decoder. Flat UI
+//! graphics typically compress several times.
+
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::{BitDepth, WritePixels};
+
+const MAX_PACKET: usize = 128;
+
+/// Encodes pixel data of the given depth into `out`.
+///
+/// Returns the encoded length, or `None` if `out` is too small. In the worst
+/// case, the encoded data is one byte per 128 pixels longer than the input.
+/// Meant to be used on the host to prepare assets, but works in `no_std`.
+pub fn encode(data: &[u8], bit_depth: BitDepth, out: &mut [u8]) -> Option<usize> {
+    let bpp = bit_depth.bytes();
+    let pixel = |i: usize| &data[i * bpp..(i + 1) * bpp];
+    let count = data.len() / bpp;
+    let run_len = |i: usize| {
+        (i..count.min(i + MAX_PACKET))
+            .take_while(|&j| pixel(j) == pixel(i))
+            .count()
+    };
+
+    let mut len = 0;
+    let mut emit = |bytes: &[u8]| -> Option<()> {
+        out.get_mut(len..len + bytes.len())?.copy_from_slice(bytes);
+        len += bytes.len();
+        Some(())
+    };
+    let mut i = 0;
+    while i < count {
+        let run = run_len(i);
+        if run >= 2 {
+            emit(&[0x80 + (run - 1) as u8])?;
+            emit(pixel(i))?;
+            i += run;
+            continue;
+        }
+        let mut end = i + 1;
+        while end < count && end - i < MAX_PACKET && run_len(end) < 2 {
+            end += 1;
+        }
+        emit(&[(end - i - 1) as u8])?;
+        emit(&data[i * bpp..end * bpp])?;
+        i = end;
+    }
+    Some(len)
+}
+
+/// Decodes the image and sends it to the given area of the display.
+///
+/// The pixels are decoded into `buf`, which is sent whenever it's full, so a
+/// buffer of a few rows is enough. Decoding stops when the area is filled or
+/// the data ends. Truncated packets are ignored.
+///
+/// # Panics
+///
+/// If `buf` can't hold at least one pixel.
+pub async fn write_pixels(
+    display: &mut (impl WritePixels + ?Sized),
+    data: &[u8],
+    bit_depth: BitDepth,
+    area: Rectangle,
+    buf: &mut [u8],
+) {
+    let bpp = bit_depth.bytes();
+    let buf_len = buf.len() - buf.len() % bpp;
+    assert!(buf_len > 0);
+    let mut remaining = area.size.width as usize * area.size.height as usize;
+    let mut filled = 0;
+    let mut i = 0;
+    while remaining > 0 && i < data.len() {
+        let c = data[i] as usize;
+        let (count, repeat) = if c >= 0x80 {
+            (c - 0x80 + 1, true)
+        } else {
+            (c + 1, false)
+        };
+        let packet_len = if repeat { bpp } else { count * bpp };
+        let Some(src) = data.get(i + 1..i + 1 + packet_len) else {
+            break;
+        };
+        i += 1 + packet_len;
+        for k in 0..count.min(remaining) {
+            let p = if repeat {
+                src
+            } else {
+                &src[k * bpp..(k + 1) * bpp]
+            };
+            buf[filled..filled + bpp].copy_from_slice(p);
+            filled += bpp;
+            if filled == buf_len {
+                display.write_pixels(&buf[..filled], bit_depth, area).await;
+                filled = 0;
+            }
+        }
+        remaining -= count.min(remaining);
+    }
+    if filled > 0 {
+        display.write_pixels(&buf[..filled], bit_depth, area).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics_core::prelude::{Point, Size};
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::test_util::{block_on, Recorder};
+
+    fn decode(encoded: &[u8], bit_depth: BitDepth, pixels: u32, buf: &mut [u8]) -> Recorder {
+        let mut display = Recorder::default();
+        let area = Rectangle::new(Point::zero(), Size::new(pixels, 1));
+        block_on(write_pixels(&mut display, encoded, bit_depth, area, buf));
+        display
+    }
+
+    #[test]
+    fn round_trip() {
+        // A run, literals, a run longer than a packet, and literals at the end.
+        let mut data = Vec::new();
+        data.extend([7u16; 100]);
+        data.extend((0..100).map(|i| i % 7));
+        data.extend([0xABCD; 300]);
+        data.extend([1, 2, 3]);
+        let data: Vec<u8> = data.iter().flat_map(|p| p.to_be_bytes()).collect();
+        let mut encoded = [0; 1024];
+        let len = encode(&data, BitDepth::Sixteen, &mut encoded).unwrap();
+        assert!(len < data.len() / 2);
+        let display = decode(&encoded[..len], BitDepth::Sixteen, 503, &mut [0; 37]);
+        assert_eq!(display.bytes(), data);
+    }
+
+    #[test]
+    fn encoding() {
+        let mut out = [0; 16];
+        let len = encode(&[5, 5, 5, 1, 2, 3, 3], BitDepth::Eight, &mut out).unwrap();
+        assert_eq!(out[..len], [0x82, 5, 0x01, 1, 2, 0x81, 3]);
+        assert_eq!(encode(&[], BitDepth::Eight, &mut out), Some(0));
+        assert_eq!(encode(&[1, 2, 3], BitDepth::Eight, &mut out[..3]), None);
+    }
+
+    #[test]
+    fn decoding_stops_at_area_end_and_truncated_packets() {
+        let display = decode(&[0x89, 4], BitDepth::Eight, 3, &mut [0; 8]);
+        assert_eq!(display.bytes(), [4, 4, 4]);
+        let display = decode(&[0x81, 4, 0x03, 1, 2], BitDepth::Eight, 8, &mut [0; 8]);
+        assert_eq!(display.bytes(), [4, 4]);
+    }
+}
@@ -0,0 +1,155 @@
+use embedded_graphics_core::{
+    pixelcolor::raw::ToBytes,
+    prelude::{PixelColor, Point, Size},
+    primitives::Rectangle,
+};
+use heapless::Vec;
+
+use crate::{
+    geometry::{overlaps, union},
+    Framebuffer, TryWritePixels,
+};
+
+/// Sends dirty areas of a framebuffer incrementally, limiting the amount of
+/// data sent at a time.
+///
+/// Add the areas that need to be sent with [Self::add], then call
+/// [Self::tick] periodically. Each tick sends whole rows of the pending areas
+/// until the byte budget runs out, which bounds the time the SPI bus is
+/// occupied by the display. Up to `N` separate areas are tracked;
+/// overlapping areas are merged.
+pub struct FlushScheduler<const N: usize> {
+    pending: Vec<Rectangle, N>,
+    // Number of rows of the first pending area that were already sent.
+    progress: u32,
+}
+
+impl<const N: usize> Default for FlushScheduler<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> FlushScheduler<N> {
+    pub const fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            progress: 0,
+        }
+    }
+
+    /// Adds an area to send, in framebuffer coordinates.
+    ///
+    /// The area is merged with any overlapping pending area. If there's no
+    /// room for another area, it's merged with the last one instead. If the
+    /// area being sent is extended, it's sent again from the start.
+    pub fn add(&mut self, area: Rectangle) {
+        if area.is_zero_sized() {
+            return;
+        }
+        let mut area = area;
+        // Merging grows the area, which can then overlap areas that were
+        // already checked, so the scan starts over after each merge.
+        let mut i = 0;
+        while i < self.pending.len() {
+            if overlaps(&self.pending[i], &area) {
+                area = union(area, self.pending.remove(i));
+                if i == 0 {
+                    self.progress = 0;
+                }
+                i = 0;
+            } else {
+                i += 1;
+            }
+        }
+        if let Err(area) = self.pending.push(area) {
+            // The merged area can overlap the others in turn.
+            let last = self.pending.pop().unwrap();
+            if self.pending.is_empty() {
+                self.progress = 0;
+            }
+            self.add(union(last, area));
+        }
+    }
+
+    /// Whether all added areas have been sent.
+    pub fn is_idle(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Sends pending rows of up to `budget` bytes, and returns the number of
+    /// bytes sent.
+    ///
+    /// On an error, the rows that failed stay pending.
+    ///
+    /// At least one row is sent if anything is pending, even if it doesn't
+    /// fit in the budget. The framebuffer is placed at `top_left` on the
+    /// display.
+    pub async fn tick<D, C>(
+        &mut self,
+        display: &mut D,
+        fb: &Framebuffer<'_, C>,
+        top_left: Point,
+        budget: usize,
+    ) -> Result<usize, D::Error>
+    where
+        D: TryWritePixels + ?Sized,
+        C: PixelColor + ToBytes,
+    {
+        let bpp = fb.bit_depth().bytes();
+        let mut sent = 0;
+        while let Some(&area) = self.pending.first() {
+            let row_len = area.size.width as usize * bpp;
+            let fit = (budget.saturating_sub(sent) / row_len) as u32;
+            let rows = if sent == 0 { fit.max(1) } else { fit };
+            let rows = rows.min(area.size.height - self.progress);
+            if rows == 0 {
+                break;
+            }
+            let part = Rectangle::new(
+                area.top_left + Point::new(0, self.progress as i32),
+                Size::new(area.size.width, rows),
+            );
+            display
+                .try_flush_region(fb, part, top_left + part.top_left)
+                .await?;
+            sent += rows as usize * row_len;
+            self.progress += rows;
+            if self.progress == area.size.height {
+                self.pending.remove(0);
+                self.progress = 0;
+            }
+        }
+        Ok(sent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, w: u32, h: u32) -> Rectangle {
+        Rectangle::new(Point::new(x, y), Size::new(w, h))
+    }
+
+    #[test]
+    fn merged_area_is_merged_again() {
+        let mut scheduler = FlushScheduler::<4>::new();
+        scheduler.add(rect(0, 0, 4, 2));
+        scheduler.add(rect(6, 0, 2, 4));
+        assert_eq!(scheduler.pending, [rect(0, 0, 4, 2), rect(6, 0, 2, 4)]);
+        // Overlaps only the second area, but the two together overlap the
+        // first one.
+        scheduler.add(rect(2, 3, 6, 1));
+        assert_eq!(scheduler.pending, [rect(0, 0, 8, 4)]);
+    }
+
+    #[test]
+    fn area_merged_with_last_when_full_is_merged_again() {
+        let mut scheduler = FlushScheduler::<2>::new();
+        scheduler.add(rect(0, 0, 2, 2));
+        scheduler.add(rect(10, 0, 2, 2));
+        scheduler.add(rect(0, 5, 1, 1));
+        assert_eq!(scheduler.pending, [rect(0, 0, 12, 6)]);
+    }
+}
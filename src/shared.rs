@@ -0,0 +1,115 @@
+use embassy_sync::{
+    blocking_mutex::raw::RawMutex,
+    mutex::{Mutex, MutexGuard},
+};
+use embedded_graphics_core::{
+    prelude::{OriginDimensions, Point, Size},
+    primitives::Rectangle,
+};
+
+use crate::{geometry::overlaps, tiled::write_part, BitDepth, TryWritePixels, WritePixels};
+
+/// A display shared between tasks, behind an `embassy-sync` mutex.
+///
+/// Hands out [`Region`]s, each restricted to a rectangle of the display, so
+/// that e.g. a clock, a sensor graph and notifications can be updated by
+/// separate tasks. Every transfer locks the display for its duration, so
+/// transfers of different regions don't interleave, but the driver may
+/// still switch between the regions between transfers, e.g. between the
+/// rows of a flush.
+pub struct SharedDisplay<M: RawMutex, D> {
+    display: Mutex<M, D>,
+}
+
+impl<M: RawMutex, D: OriginDimensions> SharedDisplay<M, D> {
+    pub const fn new(display: D) -> Self {
+        Self {
+            display: Mutex::new(display),
+        }
+    }
+
+    /// Returns a handle for drawing to the area of the display.
+    ///
+    /// Nothing stops regions from overlapping; what's drawn last shows.
+    pub fn region(&self, area: Rectangle) -> Region<'_, M, D> {
+        Region { shared: self, area }
+    }
+
+    /// Splits the display into regions for the given areas, e.g. one per
+    /// widget of the firmware.
+    ///
+    /// Panics if the areas overlap, or aren't within the display.
+    pub async fn split<const N: usize>(&self, areas: [Rectangle; N]) -> [Region<'_, M, D>; N] {
+        let bounds = Rectangle::new(Point::zero(), self.display.lock().await.size());
+        for (i, a) in areas.iter().enumerate() {
+            assert!(bounds.intersection(a) == *a, "region outside the display");
+            assert!(
+                areas[..i].iter().all(|b| !overlaps(a, b)),
+                "regions overlap"
+            );
+        }
+        areas.map(|area| self.region(area))
+    }
+
+    /// Locks the display, e.g. for commands that affect all of it.
+    pub async fn lock(&self) -> MutexGuard<'_, M, D> {
+        self.display.lock().await
+    }
+
+    pub fn into_inner(self) -> D {
+        self.display.into_inner()
+    }
+}
+
+/// A rectangle of a [`SharedDisplay`], as a display of its own.
+///
+/// Coordinates are relative to the top-left corner of the region, and the
+/// parts of transfers outside of it are clipped.
+pub struct Region<'a, M: RawMutex, D> {
+    shared: &'a SharedDisplay<M, D>,
+    area: Rectangle,
+}
+
+impl<M: RawMutex, D> Region<'_, M, D> {
+    /// The area of the display the region covers.
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+}
+
+impl<M: RawMutex, D> OriginDimensions for Region<'_, M, D> {
+    fn size(&self) -> Size {
+        self.area.size
+    }
+}
+
+impl<M: RawMutex, D: TryWritePixels> WritePixels for Region<'_, M, D> {
+    async fn write_pixels(&mut self, data: &[u8], bit_depth: BitDepth, area: Rectangle) {
+        self.try_write_pixels(data, bit_depth, area)
+            .await
+            .unwrap_or_else(|_| panic!("write failed"))
+    }
+}
+
+impl<M: RawMutex, D: TryWritePixels> TryWritePixels for Region<'_, M, D> {
+    type Error = D::Error;
+
+    async fn try_write_pixels(
+        &mut self,
+        data: &[u8],
+        bit_depth: BitDepth,
+        area: Rectangle,
+    ) -> Result<(), Self::Error> {
+        let part = area.intersection(&Rectangle::new(Point::zero(), self.area.size));
+        let mut display = self.shared.display.lock().await;
+        write_part(
+            &mut *display,
+            data,
+            bit_depth,
+            area,
+            part,
+            self.area.top_left,
+        )
+        .await
+    }
+}
@@ -0,0 +1,206 @@
+use embedded_graphics_core::{
+    pixelcolor::raw::ToBytes,
+    prelude::{PixelColor, Point, Size},
+    primitives::Rectangle,
+};
+
+use crate::{
+    expand::write_expanded,
+    geometry::{overlaps, union},
+    BitDepth, FramebufferImage, TryAdapter, WritePixels,
+};
+
+/// Frames of a sprite, stored one after another as big-endian pixel data.
+///
+/// Pixels of the `transparent` color, if given, show the background.
+#[derive(Clone, Copy)]
+pub struct SpriteSheet<'a, C> {
+    data: &'a [u8],
+    frame_size: Size,
+    transparent: Option<C>,
+}
+
+impl<'a, C> SpriteSheet<'a, C>
+where
+    C: PixelColor + ToBytes,
+{
+    pub const fn new(data: &'a [u8], frame_size: Size, transparent: Option<C>) -> Self {
+        Self {
+            data,
+            frame_size,
+            transparent,
+        }
+    }
+
+    pub const fn frame_size(&self) -> Size {
+        self.frame_size
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.data.len().checked_div(self.frame_len()).unwrap_or(0)
+    }
+
+    fn frame_len(&self) -> usize {
+        self.frame_size.width as usize
+            * self.frame_size.height as usize
+            * core::mem::size_of::<C::Bytes>()
+    }
+
+    pub(crate) fn frame(&self, index: usize) -> &'a [u8] {
+        &self.data[index * self.frame_len()..][..self.frame_len()]
+    }
+}
+
+/// What the display shows where there's no sprite.
+#[derive(Clone, Copy)]
+pub enum Background<'a, C> {
+    Color(C),
+    /// An image with its top-left corner at the display origin. The area
+    /// outside the image is black.
+    Image(FramebufferImage<'a, C>),
+}
+
+/// A sprite on the display that erases itself when it moves.
+///
+/// Change the position, frame or visibility, and call [Self::update] to
+/// send the changes: the area the sprite left is restored from the
+/// background, and the sprite is drawn at its new location. If the old and
+/// the new location overlap, both are sent as a single rectangle. Nothing
+/// else needs to be in RAM, but sprites drawn this way don't know about
+/// each other, so overlapping sprites erase each other.
+pub struct Sprite<'a, C> {
+    sheet: SpriteSheet<'a, C>,
+    frame: usize,
+    position: Point,
+    visible: bool,
+    // Area the sprite was last drawn at, if any.
+    drawn: Option<Rectangle>,
+    changed: bool,
+}
+
+impl<'a, C> Sprite<'a, C>
+where
+    C: PixelColor + ToBytes,
+    C::Bytes: AsRef<[u8]>,
+{
+    /// Creates a visible sprite showing the first frame. Nothing is drawn
+    /// until the first [Self::update].
+    pub fn new(sheet: SpriteSheet<'a, C>, position: Point) -> Self {
+        Self {
+            sheet,
+            frame: 0,
+            position,
+            visible: true,
+            drawn: None,
+            changed: true,
+        }
+    }
+
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Point) {
+        self.changed |= position != self.position;
+        self.position = position;
+    }
+
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Selects the frame of the sprite sheet to show.
+    ///
+    /// Panics if the sheet doesn't have the frame.
+    pub fn set_frame(&mut self, frame: usize) {
+        assert!(frame < self.sheet.frame_count());
+        self.changed |= frame != self.frame;
+        self.frame = frame;
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.changed |= visible != self.visible;
+        self.visible = visible;
+    }
+
+    /// Marks the sprite as changed, so that the next [Self::update] draws
+    /// it even if it didn't move, e.g. after the screen was cleared.
+    pub fn invalidate(&mut self) {
+        self.changed = true;
+    }
+
+    /// Sends the changes since the last call to the display.
+    ///
+    /// Parts outside the display are skipped. The pixels are composed into
+    /// `buf` as many whole rows at a time as it can hold.
+    ///
+    /// # Panics
+    ///
+    /// If `buf` can't hold a row of the updated area.
+    pub async fn update(
+        &mut self,
+        display: &mut (impl WritePixels + ?Sized),
+        background: &Background<'_, C>,
+        buf: &mut [u8],
+    ) {
+        if !core::mem::take(&mut self.changed) {
+            return;
+        }
+        let area = self
+            .visible
+            .then(|| Rectangle::new(self.position, self.sheet.frame_size));
+        match (self.drawn, area) {
+            (Some(old), Some(new)) if overlaps(&old, &new) => {
+                self.draw(display, union(old, new), background, buf).await;
+            }
+            (old, new) => {
+                for area in [old, new].into_iter().flatten() {
+                    self.draw(display, area, background, buf).await;
+                }
+            }
+        }
+        self.drawn = area;
+    }
+
+    // Sends the area of the display, composed of the sprite (if visible) and
+    // the background.
+    async fn draw(
+        &self,
+        display: &mut (impl WritePixels + ?Sized),
+        area: Rectangle,
+        background: &Background<'_, C>,
+        buf: &mut [u8],
+    ) {
+        let area = area.intersection(&Rectangle::new(Point::zero(), display.size()));
+        if area.is_zero_sized() {
+            return;
+        }
+        let bit_depth = BitDepth::of::<C>();
+        let bpp = bit_depth.bytes();
+        let sprite = Rectangle::new(self.position, self.sheet.frame_size);
+        let frame = self.sheet.frame(self.frame);
+        let transparent = self.sheet.transparent.map(|c| c.to_be_bytes());
+        let Ok(()) = write_expanded(&mut TryAdapter(display), area, bit_depth, buf, |y, row| {
+            for (x, dst) in row.chunks_mut(bpp).enumerate() {
+                let p = area.top_left + Point::new(x as i32, y as i32);
+                let sprite_pixel = (self.visible && sprite.contains(p))
+                    .then(|| {
+                        let q = p - sprite.top_left;
+                        let offset =
+                            (q.y as usize * sprite.size.width as usize + q.x as usize) * bpp;
+                        &frame[offset..offset + bpp]
+                    })
+                    .filter(|pixel| transparent.as_ref().map(|t| t.as_ref()) != Some(*pixel));
+                match (sprite_pixel, background) {
+                    (Some(pixel), _) => dst.copy_from_slice(pixel),
+                    (None, Background::Color(c)) => dst.copy_from_slice(c.to_be_bytes().as_ref()),
+                    (None, Background::Image(image)) => match image.pixel_bytes(p) {
+                        Some(pixel) => dst.copy_from_slice(pixel),
+                        None => dst.fill(0),
+                    },
+                }
+            }
+        })
+        .await;
+    }
+}
@@ -0,0 +1,240 @@
+use core::marker::PhantomData;
+
+use embedded_graphics_core::{
+    pixelcolor::raw::ToBytes,
+    prelude::{Dimensions, DrawTarget, OriginDimensions, PixelColor, Point, PointsIter, Size},
+    primitives::Rectangle,
+    Drawable, Pixel,
+};
+use embedded_hal::digital::OutputPin;
+
+use crate::{BitDepth, DataCommand, Error, Ssd1331};
+
+// Size of the header of a staged region: x, y, width and height.
+const HEADER: usize = 4;
+
+/// Error of a [`StagedTarget`] whose scratch buffer can't hold more pixels.
+///
+/// The pixels drawn before the error stay staged. [`StagedTarget::draw`]
+/// handles this by committing them and drawing the rest.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StagingFull;
+
+/// A [`DrawTarget`] drawing directly to the display, returned by
+/// [`Ssd1331::draw_target`].
+///
+/// Drawn pixels are staged in the scratch buffer as rectangular regions,
+/// and sent by [`Self::commit`]. Horizontal runs of pixels, and areas
+/// filled at once (e.g. images and filled rectangles), are kept as single
+/// regions, so staging them costs 4 bytes on top of the pixel data; other
+/// shapes cost that much per pixel. Pixels outside the display are skipped.
+pub struct StagedTarget<'a, RST, DI, C> {
+    pub(crate) display: &'a mut Ssd1331<RST, DI>,
+    buf: &'a mut [u8],
+    len: usize,
+    // Offset of the last region's header, while it can be extended.
+    run: Option<usize>,
+    // Pixels drawn in the current pass of [Self::draw], and the ones to skip
+    // as they were sent after an earlier pass.
+    seen: usize,
+    skip: usize,
+    color: PhantomData<C>,
+}
+
+impl<'a, RST, DI, C> StagedTarget<'a, RST, DI, C>
+where
+    C: PixelColor + ToBytes,
+    C::Bytes: AsRef<[u8]>,
+{
+    pub(crate) fn new(display: &'a mut Ssd1331<RST, DI>, buf: &'a mut [u8]) -> Self {
+        Self {
+            display,
+            buf,
+            len: 0,
+            run: None,
+            seen: 0,
+            skip: 0,
+            color: PhantomData,
+        }
+    }
+
+    /// Returns true if nothing is staged.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Sends the staged regions to the display, and clears the buffer.
+    pub async fn commit<PinE, SpiE>(&mut self) -> Result<(), Error<PinE, SpiE>>
+    where
+        RST: OutputPin<Error = PinE>,
+        DI: DataCommand<Error = Error<PinE, SpiE>>,
+    {
+        let len = core::mem::take(&mut self.len);
+        self.run = None;
+        let bpp = BitDepth::of::<C>().bytes();
+        let mut offset = 0;
+        while offset < len {
+            let [x, y, w, h] = self.buf[offset..offset + HEADER] else {
+                unreachable!()
+            };
+            let area = Rectangle::new(
+                Point::new(x.into(), y.into()),
+                Size::new(w.into(), h.into()),
+            );
+            let data_len = w as usize * h as usize * bpp;
+            let data = &self.buf[offset + HEADER..][..data_len];
+            self.display
+                .write_pixels(data, BitDepth::of::<C>(), area)
+                .await?;
+            offset += HEADER + data_len;
+        }
+        Ok(())
+    }
+
+    /// Draws the drawable, committing the staged pixels whenever the
+    /// scratch buffer gets full, and at the end.
+    ///
+    /// Each time the buffer gets full, the drawable is drawn again from the
+    /// start, skipping the pixels already sent, so it needs to draw the same
+    /// pixels in the same order every time.
+    ///
+    /// # Panics
+    ///
+    /// If the scratch buffer can't hold a single pixel.
+    pub async fn draw<D, PinE, SpiE>(&mut self, drawable: &D) -> Result<(), Error<PinE, SpiE>>
+    where
+        D: Drawable<Color = C>,
+        RST: OutputPin<Error = PinE>,
+        DI: DataCommand<Error = Error<PinE, SpiE>>,
+    {
+        loop {
+            self.seen = 0;
+            let result = drawable.draw(self);
+            let seen = self.seen;
+            if result.is_err() {
+                assert!(!self.is_empty());
+            }
+            self.commit().await?;
+            match result {
+                Ok(_) => {
+                    self.skip = 0;
+                    return Ok(());
+                }
+                Err(StagingFull) => self.skip = seen,
+            }
+        }
+    }
+
+    // Starts a region for the area, which needs to be inside the display,
+    // and returns its pixel data.
+    fn stage(&mut self, area: Rectangle) -> Result<&mut [u8], StagingFull> {
+        let bpp = BitDepth::of::<C>().bytes();
+        let data_len = area.size.width as usize * area.size.height as usize * bpp;
+        let start = self.len;
+        let region = self
+            .buf
+            .get_mut(start..start + HEADER + data_len)
+            .ok_or(StagingFull)?;
+        region[..HEADER].copy_from_slice(&[
+            area.top_left.x as u8,
+            area.top_left.y as u8,
+            area.size.width as u8,
+            area.size.height as u8,
+        ]);
+        self.len += HEADER + data_len;
+        self.run = (area.size.height == 1).then_some(start);
+        Ok(&mut region[HEADER..])
+    }
+
+    fn draw_pixel(&mut self, p: Point, color: C) -> Result<(), StagingFull> {
+        if self.seen < self.skip {
+            self.seen += 1;
+            return Ok(());
+        }
+        let bytes = color.to_be_bytes();
+        let bytes = bytes.as_ref();
+        // Extends the last run if the pixel continues it.
+        if let Some(run) = self.run {
+            let [x, y, w, _] = self.buf[run..run + HEADER] else {
+                unreachable!()
+            };
+            if p == Point::new(i32::from(x) + i32::from(w), y.into())
+                && w < u8::MAX
+                && self.len + bytes.len() <= self.buf.len()
+            {
+                self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+                self.buf[run + 2] += 1;
+                self.len += bytes.len();
+                self.seen += 1;
+                return Ok(());
+            }
+        }
+        self.stage(Rectangle::new(p, Size::new(1, 1)))?
+            .copy_from_slice(bytes);
+        self.seen += 1;
+        Ok(())
+    }
+}
+
+impl<RST, DI, C> Dimensions for StagedTarget<'_, RST, DI, C> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(Point::zero(), self.display.size())
+    }
+}
+
+impl<RST, DI, C> DrawTarget for StagedTarget<'_, RST, DI, C>
+where
+    C: PixelColor + ToBytes,
+    C::Bytes: AsRef<[u8]>,
+{
+    type Color = C;
+    type Error = StagingFull;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounds = self.bounding_box();
+        for Pixel(p, color) in pixels {
+            if bounds.contains(p) {
+                self.draw_pixel(p, color)?;
+            } else {
+                self.seen += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let count = area.size.width as usize * area.size.height as usize;
+        if count == 0 {
+            return Ok(());
+        }
+        if self.seen + count <= self.skip {
+            self.seen += count;
+            return Ok(());
+        }
+        // Stages the area as a single region if it can, or pixel by pixel
+        // otherwise.
+        let whole = self.seen >= self.skip
+            && area.intersection(&self.bounding_box()) == *area
+            && self.stage(*area).is_ok();
+        if !whole {
+            return self.draw_iter(area.points().zip(colors).map(|(p, c)| Pixel(p, c)));
+        }
+        let bpp = BitDepth::of::<C>().bytes();
+        let data = &mut self.buf[self.len - count * bpp..self.len];
+        for (dst, color) in data.chunks_mut(bpp).zip(colors) {
+            dst.copy_from_slice(color.to_be_bytes().as_ref());
+        }
+        self.seen += count;
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_contiguous(area, core::iter::repeat(color))
+    }
+}
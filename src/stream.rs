@@ -0,0 +1,101 @@
+use embedded_graphics_core::primitives::Rectangle;
+use embedded_io_async::Read;
+
+use crate::{BitDepth, WritePixels};
+
+/// Copies pixel data from a reader to the given area of the display.
+///
+/// Useful for images that don't fit in RAM, e.g. stored in an external
+/// flash or on an SD card. The data is read into `buf` and sent in chunks,
+/// until the area is filled or the reader reaches the end of the data.
+/// Returns the number of bytes sent.
+///
+/// # Panics
+///
+/// If `buf` can't hold at least one pixel.
+pub async fn write_pixels_from_reader<R: Read>(
+    display: &mut (impl WritePixels + ?Sized),
+    mut reader: R,
+    bit_depth: BitDepth,
+    area: Rectangle,
+    buf: &mut [u8],
+) -> Result<usize, R::Error> {
+    let bpp = bit_depth.bytes();
+    assert!(buf.len() >= bpp);
+    let total = area.size.width as usize * area.size.height as usize * bpp;
+    let mut sent = 0;
+    // Bytes of an incomplete pixel left over from the previous read.
+    let mut carry = 0;
+    while sent < total {
+        // The carried bytes count towards the remaining ones, so nothing
+        // past the area is read.
+        let limit = buf.len().min(total - sent);
+        let n = reader.read(&mut buf[carry..limit]).await?;
+        if n == 0 {
+            break;
+        }
+        let len = carry + n;
+        let whole = len - len % bpp;
+        if whole > 0 {
+            display.write_pixels(&buf[..whole], bit_depth, area).await;
+            sent += whole;
+        }
+        buf.copy_within(whole..len, 0);
+        carry = len - whole;
+    }
+    Ok(sent)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+    use std::vec::Vec;
+
+    use embedded_graphics_core::prelude::{Point, Size};
+    use embedded_io_async::ErrorType;
+
+    use super::*;
+    use crate::test_util::{block_on, Recorder};
+
+    // Returns at most `chunk` bytes per read.
+    struct ChunkedReader<'a> {
+        data: &'a [u8],
+        chunk: usize,
+    }
+
+    impl ErrorType for ChunkedReader<'_> {
+        type Error = Infallible;
+    }
+
+    impl Read for ChunkedReader<'_> {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+            let n = buf.len().min(self.chunk).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn odd_sized_reads_stop_at_area_end() {
+        let data: Vec<u8> = (0..40).collect();
+        let mut reader = ChunkedReader {
+            data: &data,
+            chunk: 9,
+        };
+        let mut display = Recorder::default();
+        let area = Rectangle::new(Point::zero(), Size::new(4, 4));
+        let sent = block_on(write_pixels_from_reader(
+            &mut display,
+            &mut reader,
+            BitDepth::Sixteen,
+            area,
+            &mut [0; 64],
+        ));
+        assert_eq!(sent, Ok(32));
+        assert_eq!(display.bytes(), data[..32]);
+        assert!(display.writes.iter().all(|(w, _)| w.len() % 2 == 0));
+        // The data after the area is left in the reader.
+        assert_eq!(reader.data, &data[32..]);
+    }
+}
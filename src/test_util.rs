@@ -0,0 +1,65 @@
+//! Helpers for the unit tests.
+
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+use std::vec::Vec;
+
+use embedded_graphics_core::{
+    prelude::{OriginDimensions, Size},
+    primitives::Rectangle,
+};
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{BitDepth, WritePixels};
+
+/// Polls the future to completion, for futures that never wait.
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// A delay that returns right away.
+pub(crate) struct NoDelay;
+
+impl DelayNs for NoDelay {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
+
+/// A 96x64 display that records the writes.
+#[derive(Default)]
+pub(crate) struct Recorder {
+    pub writes: Vec<(Vec<u8>, Rectangle)>,
+}
+
+impl Recorder {
+    /// All the data written, in order.
+    pub fn bytes(&self) -> Vec<u8> {
+        self.writes
+            .iter()
+            .flat_map(|(data, _)| data.clone())
+            .collect()
+    }
+
+    /// The areas written, in order.
+    pub fn areas(&self) -> Vec<Rectangle> {
+        self.writes.iter().map(|&(_, area)| area).collect()
+    }
+}
+
+impl OriginDimensions for Recorder {
+    fn size(&self) -> Size {
+        Size::new(96, 64)
+    }
+}
+
+impl WritePixels for Recorder {
+    async fn write_pixels(&mut self, data: &[u8], _bit_depth: BitDepth, area: Rectangle) {
+        self.writes.push((data.to_vec(), area));
+    }
+}
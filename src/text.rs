@@ -0,0 +1,750 @@
+//! Text output with packed monospace fonts.
+//!
+//! The glyphs are unpacked straight into a small buffer and sent to the
+//! display one at a time, so drawing text needs neither a framebuffer nor
+//! `embedded-graphics` text styles.
+
+use core::ops::Range;
+
+use embedded_graphics_core::{
+    pixelcolor::raw::ToBytes,
+    prelude::{PixelColor, Point, Size},
+    primitives::Rectangle,
+};
+
+#[cfg(feature = "mono-font")]
+use embedded_graphics::mono_font::MonoFont;
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{BitDepth, DataCommand, Error, Ssd1331, WritePixels};
+
+/// Monospace bitmap font in character-major packed format.
+///
+/// The glyphs are stored one after another, starting with the space
+/// character. Each glyph is the bits of its pixels in row order, least
+/// significant bit first, padded to a whole byte; a set bit is foreground.
+/// This is e.g. an `embedded-graphics` mono font with the glyphs cut out of
+/// the atlas and laid out consecutively.
+#[derive(Clone, Copy)]
+pub struct PackedFont<'a> {
+    data: &'a [u8],
+    char_size: Size,
+}
+
+impl<'a> PackedFont<'a> {
+    pub const fn new(data: &'a [u8], char_size: Size) -> Self {
+        Self { data, char_size }
+    }
+
+    pub const fn char_size(&self) -> Size {
+        self.char_size
+    }
+
+    // Number of bytes of a packed glyph.
+    const fn glyph_len(&self) -> usize {
+        (self.char_size.width as usize * self.char_size.height as usize).div_ceil(8)
+    }
+
+    // Returns the packed glyph of the character, if the font has it.
+    fn glyph(&self, c: char) -> Option<&'a [u8]> {
+        let index = (c as usize).checked_sub(' ' as usize)?;
+        let start = index * self.glyph_len();
+        self.data.get(start..start + self.glyph_len())
+    }
+
+    // Unpacks the glyph into `buf`, with the pixels given as big-endian
+    // bytes. Characters missing from the font are drawn as '?', or left
+    // blank if that's missing as well.
+    pub(crate) fn unpack(&self, c: char, buf: &mut [u8], fg: &[u8], bg: &[u8]) {
+        let len = fg.len();
+        let pixels = buf[..self.char_size.width as usize * self.char_size.height as usize * len]
+            .chunks_mut(len);
+        match self.glyph(c).or_else(|| self.glyph('?')) {
+            Some(glyph) => {
+                for (i, pixel) in pixels.enumerate() {
+                    let on = glyph[i / 8] >> (i % 8) & 1 == 1;
+                    pixel.copy_from_slice(if on { fg } else { bg });
+                }
+            }
+            None => pixels.for_each(|pixel| pixel.copy_from_slice(bg)),
+        }
+    }
+}
+
+/// Number of bytes of the [`PackedFont`] data repacked from the font atlas by
+/// [`pack_atlas`].
+///
+/// `atlas_len` is the length of the atlas data in bytes. Only whole glyphs
+/// are counted.
+pub const fn packed_len(atlas_len: usize, atlas_width: u32, char_size: Size) -> usize {
+    let (width, height) = (char_size.width as usize, char_size.height as usize);
+    if width == 0 || height == 0 {
+        return 0;
+    }
+    let atlas_height = atlas_len / (atlas_width as usize).div_ceil(8);
+    let glyphs = (atlas_width as usize / width) * (atlas_height / height);
+    glyphs * (width * height).div_ceil(8)
+}
+
+/// Repacks a font atlas into the [`PackedFont`] format.
+///
+/// The atlas is a 1-bit image `atlas_width` pixels wide, with each row
+/// padded to a whole byte and the most significant bit first, as used by
+/// `ImageRaw<BinaryColor>` in `embedded-graphics`. The glyphs are laid out
+/// in a grid, left to right and then top to bottom, starting with the space
+/// character. `N` is the [`packed_len`] of the atlas; glyphs past it are
+/// skipped. Usually called through [`include_packed_font!`].
+///
+/// [`include_packed_font!`]: crate::include_packed_font
+pub const fn pack_atlas<const N: usize>(
+    atlas: &[u8],
+    atlas_width: u32,
+    char_size: Size,
+) -> [u8; N] {
+    let mut out = [0; N];
+    let (width, height) = (char_size.width as usize, char_size.height as usize);
+    if width == 0 || height == 0 {
+        return out;
+    }
+    let row_len = (atlas_width as usize).div_ceil(8);
+    let columns = atlas_width as usize / width;
+    let glyph_len = (width * height).div_ceil(8);
+    let mut glyph = 0;
+    while glyph < N / glyph_len {
+        let (x0, y0) = (glyph % columns * width, glyph / columns * height);
+        let mut i = 0;
+        while i < width * height {
+            let (x, y) = (x0 + i % width, y0 + i / width);
+            if atlas[y * row_len + x / 8] & (0x80 >> (x % 8)) != 0 {
+                out[glyph * glyph_len + i / 8] |= 1 << (i % 8);
+            }
+            i += 1;
+        }
+        glyph += 1;
+    }
+    out
+}
+
+/// Includes a font atlas file as a [`PackedFont`], repacking it at compile
+/// time.
+///
+/// Takes the path of the file relative to the current file, the width of
+/// the atlas in pixels, and the character size. See [`pack_atlas`] for the
+/// atlas format; e.g. the `.raw` font images of `embedded-graphics` can be
+/// used as they are.
+///
+/// ```ignore
+/// static FONT: PackedFont = include_packed_font!("font_6x12.raw", 96, Size::new(6, 12));
+/// ```
+#[macro_export]
+macro_rules! include_packed_font {
+    ($path:expr, $atlas_width:expr, $char_size:expr $(,)?) => {{
+        const ATLAS: &[u8] = include_bytes!($path);
+        const LEN: usize = $crate::text::packed_len(ATLAS.len(), $atlas_width, $char_size);
+        const DATA: [u8; LEN] = $crate::text::pack_atlas(ATLAS, $atlas_width, $char_size);
+        $crate::text::PackedFont::new(&DATA, $char_size)
+    }};
+}
+
+/// Number of bytes of the [`PackedFont`] data repacked from an
+/// `embedded-graphics` mono font by [`pack_mono_font`].
+#[cfg(feature = "mono-font")]
+pub fn mono_font_packed_len(font: &MonoFont<'_>, last: char) -> usize {
+    let size = font.character_size;
+    let glyph_len = (size.width as usize * size.height as usize).div_ceil(8);
+    (last as usize + 1).saturating_sub(' ' as usize) * glyph_len
+}
+
+/// Repacks the glyphs of an `embedded-graphics` mono font, from the space
+/// character through `last`, into `out` in the [`PackedFont`] format.
+///
+/// Characters the font doesn't have get its replacement glyph. Returns
+/// `None` if `out` is shorter than [`mono_font_packed_len`].
+///
+/// The stock fonts can't be read in const context, so to keep the glyphs in
+/// flash, call this from a build script (with this crate as a build
+/// dependency) and include the result:
+///
+/// ```ignore
+/// // build.rs
+/// let mut data = vec![0; mono_font_packed_len(&FONT_6X12, '~')];
+/// pack_mono_font(&FONT_6X12, '~', &mut data).unwrap();
+/// std::fs::write(Path::new(&env::var("OUT_DIR")?).join("font.bin"), data)?;
+///
+/// // main.rs
+/// static FONT: PackedFont =
+///     PackedFont::new(include_bytes!(concat!(env!("OUT_DIR"), "/font.bin")), Size::new(6, 12));
+/// ```
+#[cfg(feature = "mono-font")]
+pub fn pack_mono_font<'b>(
+    font: &MonoFont<'_>,
+    last: char,
+    out: &'b mut [u8],
+) -> Option<PackedFont<'b>> {
+    use embedded_graphics_core::{
+        image::GetPixel, pixelcolor::BinaryColor, prelude::OriginDimensions,
+    };
+
+    let size = font.character_size;
+    let (width, height) = (size.width as usize, size.height as usize);
+    let columns = (font.image.size().width / size.width.max(1)) as usize;
+    let out = out.get_mut(..mono_font_packed_len(font, last))?;
+    out.fill(0);
+    let glyph_len = (width * height).div_ceil(8);
+    for (glyph, data) in out.chunks_mut(glyph_len).enumerate() {
+        let c = char::from_u32(' ' as u32 + glyph as u32).unwrap_or('?');
+        let index = font.glyph_mapping.index(c);
+        let top_left = Point::new(
+            (index % columns * width) as i32,
+            (index / columns * height) as i32,
+        );
+        for i in 0..width * height {
+            let p = top_left + Point::new((i % width) as i32, (i / width) as i32);
+            if font.image.pixel(p) == Some(BinaryColor::On) {
+                data[i / 8] |= 1 << (i % 8);
+            }
+        }
+    }
+    Some(PackedFont::new(out, size))
+}
+
+/// Draws strings at arbitrary positions, one glyph per transfer.
+///
+/// Works with any color type the display takes, e.g. [`Rgb565`] or
+/// [`Rgb332`](crate::Rgb332); only a buffer of one glyph in that color depth
+/// is needed.
+///
+/// [`Rgb565`]: embedded_graphics_core::pixelcolor::Rgb565
+#[derive(Clone, Copy)]
+pub struct TextRenderer<'a, C> {
+    font: PackedFont<'a>,
+    fg: C,
+    bg: C,
+}
+
+impl<'a, C> TextRenderer<'a, C>
+where
+    C: PixelColor + ToBytes,
+    C::Bytes: AsRef<[u8]>,
+{
+    pub const fn new(font: PackedFont<'a>, fg: C, bg: C) -> Self {
+        Self { font, fg, bg }
+    }
+
+    pub const fn font(&self) -> PackedFont<'a> {
+        self.font
+    }
+
+    pub fn set_colors(&mut self, fg: C, bg: C) {
+        self.fg = fg;
+        self.bg = bg;
+    }
+
+    /// Draws the text on a single line with its top-left corner at
+    /// `top_left`. Control characters aren't interpreted.
+    ///
+    /// The `buf` is used to unpack the glyphs, and needs to hold one glyph in
+    /// the renderer's color depth.
+    ///
+    /// Panics if `buf` is too small.
+    pub async fn draw(
+        &self,
+        display: &mut (impl WritePixels + ?Sized),
+        text: &str,
+        top_left: Point,
+        buf: &mut [u8],
+    ) {
+        let size = self.font.char_size;
+        let fg = self.fg.to_be_bytes();
+        let bg = self.bg.to_be_bytes();
+        let len = size.width as usize * size.height as usize * fg.as_ref().len();
+        for (i, c) in text.chars().enumerate() {
+            self.font.unpack(c, buf, fg.as_ref(), bg.as_ref());
+            let top_left = top_left + Point::new((i as u32 * size.width) as i32, 0);
+            display
+                .write_pixels(
+                    &buf[..len],
+                    BitDepth::of::<C>(),
+                    Rectangle::new(top_left, size),
+                )
+                .await;
+        }
+    }
+}
+
+/// Scrolling text console in a region of the display.
+///
+/// Text is written with [Self::write_str], wrapping at the right edge of the
+/// region. Once the bottom line is full, the contents scroll up by a line.
+/// The characters on screen are kept in a caller-provided buffer of one byte
+/// per character cell, so that the region can be redrawn after scrolling
+/// without a framebuffer. Only ASCII is stored; other characters are shown
+/// as '?'.
+///
+/// The terminal also implements [`core::fmt::Write`], so formatted text can
+/// be written with `write!`. As that can't send anything, the text is only
+/// stored, and drawn by the next [Self::flush_pending].
+pub struct Terminal<'a, C> {
+    font: PackedFont<'a>,
+    top_left: Point,
+    cells: &'a mut [u8],
+    columns: usize,
+    rows: usize,
+    // Position of the next character. The column can be one past the last,
+    // in which case the line wraps before the next character.
+    row: usize,
+    column: usize,
+    // Cells written but not drawn yet.
+    pending: Range<usize>,
+    // Whether the contents scrolled, so the whole region needs to be redrawn.
+    scrolled: bool,
+    fg: C,
+    bg: C,
+}
+
+impl<'a, C> Terminal<'a, C>
+where
+    C: PixelColor + ToBytes,
+    C::Bytes: AsRef<[u8]>,
+{
+    /// Creates a terminal showing as many lines of text as fit in `area`.
+    ///
+    /// The `cells` buffer needs a byte for each character that fits in the
+    /// area. The terminal starts out empty, but nothing is drawn until the
+    /// first write or [Self::clear].
+    ///
+    /// Panics if `cells` is too small.
+    pub fn new(font: PackedFont<'a>, area: Rectangle, cells: &'a mut [u8], fg: C, bg: C) -> Self {
+        let columns = (area.size.width / font.char_size.width) as usize;
+        let rows = (area.size.height / font.char_size.height) as usize;
+        let cells = &mut cells[..columns * rows];
+        cells.fill(b' ');
+        Self {
+            font,
+            top_left: area.top_left,
+            cells,
+            columns,
+            rows,
+            row: 0,
+            column: 0,
+            pending: 0..0,
+            scrolled: false,
+            fg,
+            bg,
+        }
+    }
+
+    /// Sets the colors for the text written from now on.
+    ///
+    /// Only the characters are stored, so a redraw (including the one after
+    /// scrolling) shows all the text in the current colors.
+    pub fn set_colors(&mut self, fg: C, bg: C) {
+        self.fg = fg;
+        self.bg = bg;
+    }
+
+    /// Writes the text, interpreting `\n` as a new line and `\r` as a return
+    /// to the start of the line, and draws it along with any pending text.
+    ///
+    /// The `buf` is used to unpack the glyphs, and needs to hold one glyph in
+    /// the terminal's color depth. If the text scrolls the contents, the
+    /// whole region is redrawn once at the end.
+    ///
+    /// Panics if `buf` is too small.
+    pub async fn write_str(
+        &mut self,
+        display: &mut (impl WritePixels + ?Sized),
+        s: &str,
+        buf: &mut [u8],
+    ) {
+        s.chars().for_each(|c| self.put_char(c));
+        self.flush_pending(display, buf).await;
+    }
+
+    /// Draws the text written with [`core::fmt::Write`] since the last call.
+    ///
+    /// See [Self::write_str] for `buf`.
+    pub async fn flush_pending(
+        &mut self,
+        display: &mut (impl WritePixels + ?Sized),
+        buf: &mut [u8],
+    ) {
+        if self.scrolled {
+            self.redraw(display, buf).await;
+            return;
+        }
+        for cell in core::mem::replace(&mut self.pending, 0..0) {
+            self.draw_cell(display, cell, buf).await;
+        }
+    }
+
+    /// Erases the text, filling the region with the background color, and
+    /// moves to the top-left corner.
+    pub async fn clear(&mut self, display: &mut (impl WritePixels + ?Sized), buf: &mut [u8]) {
+        self.cells.fill(b' ');
+        self.row = 0;
+        self.column = 0;
+        self.redraw(display, buf).await;
+    }
+
+    /// Draws all the characters of the region again.
+    pub async fn redraw(&mut self, display: &mut (impl WritePixels + ?Sized), buf: &mut [u8]) {
+        for cell in 0..self.cells.len() {
+            self.draw_cell(display, cell, buf).await;
+        }
+        self.pending = 0..0;
+        self.scrolled = false;
+    }
+
+    // Stores the character at the current position, without drawing it.
+    fn put_char(&mut self, c: char) {
+        match c {
+            '\n' => self.new_line(),
+            '\r' => self.column = 0,
+            _ => {
+                if self.column == self.columns {
+                    self.new_line();
+                }
+                if self.columns == 0 || self.rows == 0 {
+                    return;
+                }
+                let cell = self.row * self.columns + self.column;
+                self.cells[cell] = if c.is_ascii() { c as u8 } else { b'?' };
+                self.pending = if self.pending.is_empty() {
+                    cell..cell + 1
+                } else {
+                    self.pending.start.min(cell)..self.pending.end.max(cell + 1)
+                };
+                self.column += 1;
+            }
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.column = 0;
+        if self.rows == 0 {
+            return;
+        }
+        if self.row + 1 < self.rows {
+            self.row += 1;
+            return;
+        }
+        self.cells.copy_within(self.columns.., 0);
+        let last = self.cells.len() - self.columns;
+        self.cells[last..].fill(b' ');
+        self.scrolled = true;
+    }
+
+    async fn draw_cell(
+        &self,
+        display: &mut (impl WritePixels + ?Sized),
+        cell: usize,
+        buf: &mut [u8],
+    ) {
+        let size = self.font.char_size;
+        let fg = self.fg.to_be_bytes();
+        let bg = self.bg.to_be_bytes();
+        self.font
+            .unpack(self.cells[cell] as char, buf, fg.as_ref(), bg.as_ref());
+        let (row, column) = (cell / self.columns, cell % self.columns);
+        let top_left = self.top_left
+            + Point::new(
+                (column as u32 * size.width) as i32,
+                (row as u32 * size.height) as i32,
+            );
+        let len = size.width as usize * size.height as usize * fg.as_ref().len();
+        display
+            .write_pixels(
+                &buf[..len],
+                BitDepth::of::<C>(),
+                Rectangle::new(top_left, size),
+            )
+            .await;
+    }
+}
+
+impl<C> core::fmt::Write for Terminal<'_, C>
+where
+    C: PixelColor + ToBytes,
+    C::Bytes: AsRef<[u8]>,
+{
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        s.chars().for_each(|c| self.put_char(c));
+        Ok(())
+    }
+}
+
+/// Text scrolling horizontally through a one-line region, e.g. for a status
+/// ticker.
+///
+/// The text enters at the right edge, moves left by [Self::step] pixels at
+/// a time until it has left the region, and then starts over. Only a buffer
+/// of one glyph is needed: the visible part of each glyph is unpacked and
+/// sent separately.
+pub struct Marquee<'a, C> {
+    font: PackedFont<'a>,
+    text: &'a str,
+    top_left: Point,
+    width: u32,
+    // Scroll position within the cycle of the region width followed by the
+    // text width.
+    position: u32,
+    fg: C,
+    bg: C,
+}
+
+impl<'a, C> Marquee<'a, C>
+where
+    C: PixelColor + ToBytes,
+    C::Bytes: AsRef<[u8]>,
+{
+    /// Creates a marquee in the region of the given width and the font's
+    /// height.
+    ///
+    /// Panics if the text isn't ASCII, as each byte is drawn as a glyph.
+    pub fn new(
+        font: PackedFont<'a>,
+        text: &'a str,
+        top_left: Point,
+        width: u32,
+        fg: C,
+        bg: C,
+    ) -> Self {
+        assert!(text.is_ascii(), "marquee text must be ASCII");
+        Self {
+            font,
+            text,
+            top_left,
+            width,
+            position: 0,
+            fg,
+            bg,
+        }
+    }
+
+    /// Moves the text left by `dx` pixels and redraws the region.
+    ///
+    /// The `buf` needs to hold one glyph in the marquee's color depth.
+    ///
+    /// Panics if `buf` is too small.
+    pub async fn step(
+        &mut self,
+        display: &mut (impl WritePixels + ?Sized),
+        dx: u32,
+        buf: &mut [u8],
+    ) {
+        self.advance(dx);
+        self.draw_columns(display, 0..self.width, buf).await;
+    }
+
+    /// Like [Self::step], but moves the region contents with the display's
+    /// copy command (see [Ssd1331::copy_area](crate::Ssd1331::copy_area)),
+    /// and only draws the `dx` columns that scroll in.
+    ///
+    /// Assumes the region shows the marquee's previous step.
+    pub async fn step_with_copy<RST, DI, PinE, SpiE>(
+        &mut self,
+        display: &mut Ssd1331<RST, DI>,
+        dx: u32,
+        delay: &mut impl DelayNs,
+        buf: &mut [u8],
+    ) -> Result<(), Error<PinE, SpiE>>
+    where
+        RST: OutputPin<Error = PinE>,
+        DI: DataCommand<Error = Error<PinE, SpiE>>,
+    {
+        self.advance(dx);
+        let dx = dx.min(self.width);
+        let height = self.font.char_size.height;
+        if dx < self.width {
+            let area = Rectangle::new(
+                self.top_left + Point::new(dx as i32, 0),
+                Size::new(self.width - dx, height),
+            );
+            display.copy_area(area, self.top_left, delay).await?;
+        }
+        self.draw_columns(display, self.width - dx..self.width, buf)
+            .await;
+        Ok(())
+    }
+
+    fn advance(&mut self, dx: u32) {
+        let text_width = self.text.len() as u32 * self.font.char_size.width;
+        // An empty cycle has nothing to scroll.
+        self.position = (self.position + dx)
+            .checked_rem(self.width + text_width)
+            .unwrap_or(0);
+    }
+
+    // Draws the given columns of the region, a glyph or a glyph-wide blank
+    // strip at a time.
+    async fn draw_columns(
+        &self,
+        display: &mut (impl WritePixels + ?Sized),
+        columns: Range<u32>,
+        buf: &mut [u8],
+    ) {
+        let Size {
+            width: char_width,
+            height,
+        } = self.font.char_size;
+        let fg = self.fg.to_be_bytes();
+        let bg = self.bg.to_be_bytes();
+        let bpp = fg.as_ref().len();
+        let cycle = self.width + self.text.len() as u32 * char_width;
+        let mut x = columns.start;
+        while x < columns.end {
+            // Column in the cycle of the blank region followed by the text.
+            let s = (self.position + x) % cycle;
+            let len = match s.checked_sub(self.width) {
+                None => {
+                    let len = (self.width - s).min(char_width).min(columns.end - x);
+                    for pixel in buf[..(len * height) as usize * bpp].chunks_mut(bpp) {
+                        pixel.copy_from_slice(bg.as_ref());
+                    }
+                    len
+                }
+                Some(t) => {
+                    let c = self.text.as_bytes()[(t / char_width) as usize] as char;
+                    let first = t % char_width;
+                    let len = (char_width - first).min(columns.end - x);
+                    self.font.unpack(c, buf, fg.as_ref(), bg.as_ref());
+                    // Keep only the visible columns of the glyph.
+                    let (row_len, n) = (char_width as usize * bpp, len as usize * bpp);
+                    for row in 0..height as usize {
+                        let start = row * row_len + first as usize * bpp;
+                        buf.copy_within(start..start + n, row * n);
+                    }
+                    len
+                }
+            };
+            let area = Rectangle::new(
+                self.top_left + Point::new(x as i32, 0),
+                Size::new(len, height),
+            );
+            display
+                .write_pixels(
+                    &buf[..(len * height) as usize * bpp],
+                    BitDepth::of::<C>(),
+                    area,
+                )
+                .await;
+            x += len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1-bit atlas of `width` x `height` pixels with rows padded to bytes,
+    // most significant bit first, with `on` deciding each pixel.
+    fn atlas(width: usize, height: usize, on: impl Fn(usize, usize) -> bool) -> std::vec::Vec<u8> {
+        let row_len = width.div_ceil(8);
+        let mut data = std::vec![0; row_len * height];
+        for y in 0..height {
+            for x in 0..width {
+                if on(x, y) {
+                    data[y * row_len + x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn packed_len_counts_whole_glyphs() {
+        // 12x4 pixel atlas, 2 bytes per row: 4 columns and 2 rows of 3x2 glyphs.
+        assert_eq!(packed_len(8, 12, Size::new(3, 2)), 8);
+        // 5x3 glyphs: 2 columns, 1 row, 2 bytes each.
+        assert_eq!(packed_len(8, 12, Size::new(5, 3)), 4);
+        assert_eq!(packed_len(8, 12, Size::new(0, 2)), 0);
+    }
+
+    #[test]
+    fn pack_atlas_matches_atlas_pixels() {
+        const SIZE: Size = Size::new(3, 2);
+        let on = |x: usize, y: usize| (x * 7 + y * 3) % 5 < 2;
+        let data = atlas(12, 4, on);
+        let packed: [u8; 8] = pack_atlas(&data, 12, SIZE);
+        let font = PackedFont::new(&packed, SIZE);
+        let mut buf = [0; 6];
+        for glyph in 0..8 {
+            let c = char::from_u32(' ' as u32 + glyph).unwrap();
+            font.unpack(c, &mut buf, &[1], &[0]);
+            let (x0, y0) = (glyph as usize % 4 * 3, glyph as usize / 4 * 2);
+            for (i, &pixel) in buf.iter().enumerate() {
+                assert_eq!(pixel == 1, on(x0 + i % 3, y0 + i / 3), "{c:?} pixel {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn pack_atlas_bit_order() {
+        // A single 4x3 glyph with only its first and last pixels set.
+        let data = atlas(4, 3, |x, y| (x, y) == (0, 0) || (x, y) == (3, 2));
+        assert_eq!(pack_atlas::<2>(&data, 4, Size::new(4, 3)), [0x01, 0x08]);
+    }
+
+    #[test]
+    fn pack_atlas_skips_glyphs_past_n() {
+        let data = atlas(12, 4, |_, _| true);
+        assert_eq!(pack_atlas::<3>(&data, 12, Size::new(3, 2)), [0x3f; 3]);
+    }
+
+    #[cfg(feature = "mono-font")]
+    #[test]
+    fn pack_mono_font_matches_rendered_text() {
+        use embedded_graphics::{
+            mono_font::{ascii::FONT_6X10, MonoTextStyle},
+            pixelcolor::BinaryColor,
+            prelude::*,
+            text::{Baseline, Text},
+        };
+
+        // Collects the pixels of one character cell.
+        struct Cell([bool; 60]);
+
+        impl OriginDimensions for Cell {
+            fn size(&self) -> Size {
+                Size::new(6, 10)
+            }
+        }
+
+        impl DrawTarget for Cell {
+            type Color = BinaryColor;
+            type Error = core::convert::Infallible;
+
+            fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+            where
+                I: IntoIterator<Item = Pixel<BinaryColor>>,
+            {
+                for Pixel(p, color) in pixels {
+                    if (0..6).contains(&p.x) && (0..10).contains(&p.y) {
+                        self.0[p.y as usize * 6 + p.x as usize] = color.is_on();
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        let mut data = [0; 95 * 8];
+        assert_eq!(mono_font_packed_len(&FONT_6X10, '~'), data.len());
+        assert!(pack_mono_font(&FONT_6X10, '~', &mut data[..10]).is_none());
+        let font = pack_mono_font(&FONT_6X10, '~', &mut data).unwrap();
+        let style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+        let mut buf = [0; 60];
+        for c in ' '..='~' {
+            let mut cell = Cell([false; 60]);
+            let mut s = [0; 4];
+            Text::with_baseline(c.encode_utf8(&mut s), Point::zero(), style, Baseline::Top)
+                .draw(&mut cell)
+                .unwrap();
+            font.unpack(c, &mut buf, &[1], &[0]);
+            let unpacked = buf.map(|pixel| pixel == 1);
+            assert_eq!(unpacked, cell.0, "{c:?}");
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use embedded_graphics_core::{
+    prelude::{OriginDimensions, Point, Size},
+    primitives::Rectangle,
+};
+
+use crate::{BitDepth, TryWritePixels, WritePixels};
+
+/// Several displays arranged in a grid, as one larger display.
+///
+/// The panels are given in row order and need to be the same size, e.g. two
+/// 96x64 modules side by side make a 192x64 display. Each write is split
+/// into the parts of the area on each panel. A part that's narrower than
+/// the area is sent one row per transfer, as its pixels aren't consecutive
+/// in the data.
+pub struct TiledDisplay<D, const N: usize> {
+    panels: [D; N],
+    columns: usize,
+}
+
+impl<D: OriginDimensions, const N: usize> TiledDisplay<D, N> {
+    /// Panics if the panels don't make whole rows of `columns`, or aren't
+    /// the same size.
+    pub fn new(panels: [D; N], columns: usize) -> Self {
+        assert!(columns > 0 && N.is_multiple_of(columns));
+        assert!(panels.iter().all(|p| p.size() == panels[0].size()));
+        Self { panels, columns }
+    }
+
+    pub fn panels(&mut self) -> &mut [D; N] {
+        &mut self.panels
+    }
+
+    /// Returns the panels.
+    pub fn release(self) -> [D; N] {
+        self.panels
+    }
+
+    // Area of the panel with the given index, in the combined coordinates.
+    fn panel_area(&self, index: usize) -> Rectangle {
+        let size = self.panels[0].size();
+        let (column, row) = (index % self.columns, index / self.columns);
+        Rectangle::new(
+            Point::new(
+                (column as u32 * size.width) as i32,
+                (row as u32 * size.height) as i32,
+            ),
+            size,
+        )
+    }
+}
+
+impl<D: OriginDimensions, const N: usize> OriginDimensions for TiledDisplay<D, N> {
+    fn size(&self) -> Size {
+        let Some(panel) = self.panels.first() else {
+            return Size::zero();
+        };
+        let size = panel.size();
+        Size::new(
+            size.width * self.columns as u32,
+            size.height * (N / self.columns) as u32,
+        )
+    }
+}
+
+impl<D: TryWritePixels, const N: usize> WritePixels for TiledDisplay<D, N> {
+    async fn write_pixels(&mut self, data: &[u8], bit_depth: BitDepth, area: Rectangle) {
+        self.try_write_pixels(data, bit_depth, area)
+            .await
+            .unwrap_or_else(|_| panic!("write failed"))
+    }
+}
+
+impl<D: TryWritePixels, const N: usize> TryWritePixels for TiledDisplay<D, N> {
+    type Error = D::Error;
+
+    /// Writes the parts of the area on each panel, in panel order.
+    ///
+    /// Areas outside the combined display are clipped rather than reported
+    /// as errors. Data shorter than the area fills its first rows, as with a
+    /// single display; longer data doesn't wrap around.
+    async fn try_write_pixels(
+        &mut self,
+        data: &[u8],
+        bit_depth: BitDepth,
+        area: Rectangle,
+    ) -> Result<(), Self::Error> {
+        for index in 0..N {
+            let panel_area = self.panel_area(index);
+            let part = area.intersection(&panel_area);
+            write_part(
+                &mut self.panels[index],
+                data,
+                bit_depth,
+                area,
+                part,
+                Point::zero() - panel_area.top_left,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+// Writes the pixels of `data` that fall into `part` of `area`, with the
+// coordinates of both moved by `offset` on the display.
+//
+// Data shorter than the area fills its first rows; longer data doesn't wrap
+// around. A part that's narrower than the area is sent one row per transfer.
+pub(crate) async fn write_part<D: TryWritePixels + ?Sized>(
+    display: &mut D,
+    data: &[u8],
+    bit_depth: BitDepth,
+    area: Rectangle,
+    part: Rectangle,
+    offset: Point,
+) -> Result<(), D::Error> {
+    if part.is_zero_sized() {
+        return Ok(());
+    }
+    let bpp = bit_depth.bytes();
+    let row_len = area.size.width as usize * bpp;
+    let skip = part.top_left - area.top_left;
+    let start = skip.y as usize * row_len + skip.x as usize * bpp;
+    let local = Rectangle::new(part.top_left + offset, part.size);
+    if part.size.width == area.size.width {
+        let end = (start + part.size.height as usize * row_len).min(data.len());
+        return match data.get(start..end) {
+            Some(data) if !data.is_empty() => {
+                display.try_write_pixels(data, bit_depth, local).await
+            }
+            _ => Ok(()),
+        };
+    }
+    let part_len = part.size.width as usize * bpp;
+    for y in 0..part.size.height {
+        let start = start + y as usize * row_len;
+        if start + part_len > data.len() {
+            break;
+        }
+        let row = Rectangle::new(
+            local.top_left + Point::new(0, y as i32),
+            Size::new(part.size.width, 1),
+        );
+        display
+            .try_write_pixels(&data[start..start + part_len], bit_depth, row)
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use embedded_graphics_core::{
+    pixelcolor::raw::ToBytes,
+    prelude::{PixelColor, Point, Size},
+    primitives::Rectangle,
+};
+
+use crate::{expand::write_expanded, BitDepth, SpriteSheet, TryAdapter, WritePixels};
+
+/// Grid of tiles from a tile sheet, e.g. a game background or a large
+/// scrollable screen.
+///
+/// The map holds one byte per tile, the index of its frame in the sheet,
+/// in row-major order. Both the map and the sheet can stay in flash: the
+/// visible part is composed straight into the transfer buffer by
+/// [Self::draw]. The map repeats in both directions, so scrolling past its
+/// edge wraps around.
+pub struct TileMap<'a, C> {
+    tiles: SpriteSheet<'a, C>,
+    map: &'a [u8],
+    columns: u32,
+    rows: u32,
+}
+
+impl<'a, C> TileMap<'a, C>
+where
+    C: PixelColor + ToBytes,
+{
+    /// Creates a tile map `columns` tiles wide, with as many rows as the
+    /// map has. The transparent color of the sheet is ignored.
+    ///
+    /// Panics if the map length isn't a multiple of `columns`, or if it
+    /// refers to a tile the sheet doesn't have.
+    pub fn new(tiles: SpriteSheet<'a, C>, map: &'a [u8], columns: u32) -> Self {
+        assert!(columns > 0 && map.len().is_multiple_of(columns as usize));
+        assert!(map.iter().all(|&i| (i as usize) < tiles.frame_count()));
+        Self {
+            tiles,
+            map,
+            columns,
+            rows: (map.len() / columns as usize) as u32,
+        }
+    }
+
+    /// Size of the whole map in pixels.
+    pub fn pixel_size(&self) -> Size {
+        let tile = self.tiles.frame_size();
+        Size::new(self.columns * tile.width, self.rows * tile.height)
+    }
+
+    /// Sends the part of the map with its top-left corner at `scroll` (in
+    /// map pixels) to the `area` of the display.
+    ///
+    /// The pixels are composed into `buf` as many whole rows at a time as it
+    /// can hold, and each batch of rows is sent with a single transfer.
+    ///
+    /// # Panics
+    ///
+    /// If `buf` can't hold a row of the area.
+    pub async fn draw(
+        &self,
+        display: &mut (impl WritePixels + ?Sized),
+        area: Rectangle,
+        scroll: Point,
+        buf: &mut [u8],
+    ) {
+        let bit_depth = BitDepth::of::<C>();
+        let bpp = bit_depth.bytes();
+        let tile = self.tiles.frame_size();
+        let (tile_width, tile_height) = (tile.width as usize, tile.height as usize);
+        let size = self.pixel_size();
+        if size.width == 0 || size.height == 0 {
+            return;
+        }
+        let x0 = scroll.x.rem_euclid(size.width as i32) as usize;
+        let y0 = scroll.y.rem_euclid(size.height as i32) as usize;
+        let Ok(()) = write_expanded(&mut TryAdapter(display), area, bit_depth, buf, |y, row| {
+            let map_y = (y0 + y as usize) % size.height as usize;
+            let map_row =
+                &self.map[map_y / tile_height * self.columns as usize..][..self.columns as usize];
+            let tile_row = map_y % tile_height;
+            let mut map_x = x0;
+            let mut row = row;
+            // Copy the runs of pixels within one tile row at a time.
+            while !row.is_empty() {
+                let (column, offset) = (map_x / tile_width, map_x % tile_width);
+                let len = (tile_width - offset).min(row.len() / bpp);
+                let frame = self.tiles.frame(map_row[column] as usize);
+                let start = (tile_row * tile_width + offset) * bpp;
+                let (dst, rest) = row.split_at_mut(len * bpp);
+                dst.copy_from_slice(&frame[start..start + len * bpp]);
+                row = rest;
+                map_x = (map_x + len) % size.width as usize;
+            }
+        })
+        .await;
+    }
+}
@@ -0,0 +1,106 @@
+//! Text with fonts from [u8g2-fonts](https://crates.io/crates/u8g2-fonts).
+
+use embedded_graphics_core::{
+    pixelcolor::raw::ToBytes,
+    prelude::{DrawTarget, PixelColor, Point, Size},
+    primitives::Rectangle,
+};
+use u8g2_fonts::{
+    types::{FontColor, VerticalPosition},
+    FontRenderer, LookupError,
+};
+
+use crate::{band_target::BandTarget, Framebuffer, WritePixels};
+
+/// Draws text with `u8g2-fonts`, one glyph at a time.
+///
+/// The fonts are rendered synchronously, so each glyph is drawn into a
+/// framebuffer in a small caller-provided buffer, which is then sent to the
+/// display. This makes large and non-ASCII fonts usable without a
+/// framebuffer of the text size.
+pub struct U8g2TextRenderer<'a, C> {
+    font: &'a FontRenderer,
+    fg: C,
+    bg: C,
+}
+
+impl<'a, C> U8g2TextRenderer<'a, C>
+where
+    C: PixelColor + ToBytes,
+    C::Bytes: AsRef<[u8]>,
+{
+    pub const fn new(font: &'a FontRenderer, fg: C, bg: C) -> Self {
+        Self { font, fg, bg }
+    }
+
+    pub fn set_colors(&mut self, fg: C, bg: C) {
+        self.fg = fg;
+        self.bg = bg;
+    }
+
+    /// Draws a line of text at `position`, aligned vertically as given.
+    ///
+    /// Each glyph is sent as a cell spanning its advance (or its bounding
+    /// box, if wider) horizontally and the bounding box of the whole text
+    /// vertically, filled with the background color. Returns the area
+    /// covered by the cells, or `None` if the text has no visible glyphs.
+    /// Parts of glyphs overhanging into the previous cell are clipped.
+    ///
+    /// The `buf` needs to hold the largest cell in the renderer's color
+    /// depth.
+    ///
+    /// # Panics
+    ///
+    /// If `buf` is too small.
+    pub async fn draw(
+        &self,
+        display: &mut (impl WritePixels + ?Sized),
+        text: &str,
+        position: Point,
+        vertical_pos: VerticalPosition,
+        buf: &mut [u8],
+    ) -> Result<Option<Rectangle>, LookupError> {
+        let dimensions = self
+            .font
+            .get_rendered_dimensions(text, position, vertical_pos)?;
+        let Some(text_box) = dimensions.bounding_box else {
+            return Ok(None);
+        };
+        let (top, height) = (text_box.top_left.y, text_box.size.height);
+        let bpp = core::mem::size_of::<C::Bytes>();
+        let mut pen = position;
+        let left = position.x.min(text_box.top_left.x);
+        let mut right = left;
+        for c in text.chars() {
+            let glyph = self.font.get_rendered_dimensions(c, pen, vertical_pos)?;
+            let mut end = pen.x + glyph.advance.x;
+            if let Some(glyph_box) = glyph.bounding_box {
+                end = end.max(glyph_box.top_left.x + glyph_box.size.width as i32);
+            }
+            if end > right {
+                let cell = Rectangle::new(
+                    Point::new(right, top),
+                    Size::new((end - right) as u32, height),
+                );
+                let len = cell.size.width as usize * height as usize * bpp;
+                let mut fb = Framebuffer::<C>::new(&mut buf[..len], cell.size);
+                let _ = fb.clear(self.bg);
+                // The glyph was found above, and the target can't fail.
+                let _ = self.font.render(
+                    c,
+                    pen,
+                    vertical_pos,
+                    FontColor::Transparent(self.fg),
+                    &mut BandTarget::new(&mut fb, cell.top_left),
+                );
+                display.flush(&fb, cell.top_left).await;
+                right = end;
+            }
+            pen += glyph.advance;
+        }
+        Ok(Some(Rectangle::new(
+            Point::new(left, top),
+            Size::new((right - left) as u32, height),
+        )))
+    }
+}
@@ -0,0 +1,108 @@
+use embedded_graphics_core::{
+    pixelcolor::raw::ToBytes,
+    prelude::{Dimensions, DrawTarget, PixelColor, Point, PointsIter},
+    primitives::Rectangle,
+    Pixel,
+};
+
+use crate::Framebuffer;
+
+impl<'b, C: ToBytes> Framebuffer<'b, C> {
+    /// Returns a [`DrawTarget`] for a rectangular window of the framebuffer.
+    ///
+    /// The view has its own origin at the top-left corner of `area`, and
+    /// clips everything drawn to it to the area (and the framebuffer
+    /// bounds). Drawing through the view uses the framebuffer's fast paths,
+    /// and marks the framebuffer dirty as usual.
+    pub fn sub_view(&mut self, area: Rectangle) -> FramebufferView<'_, 'b, C> {
+        let clip = area.intersection(&self.bounding_box());
+        FramebufferView {
+            fb: self,
+            offset: area.top_left,
+            clip,
+        }
+    }
+
+    /// Returns a [`DrawTarget`] that draws to the framebuffer with the
+    /// origin moved to `offset`.
+    ///
+    /// Unlike the generic `translated` adapter from `embedded-graphics`, this
+    /// keeps the framebuffer's fast paths for filled areas.
+    pub fn translated(&mut self, offset: Point) -> FramebufferView<'_, 'b, C> {
+        let clip = self.bounding_box();
+        FramebufferView {
+            fb: self,
+            offset,
+            clip,
+        }
+    }
+}
+
+/// A window of a [`Framebuffer`] with its own origin and clipping.
+///
+/// See [Framebuffer::sub_view] and [Framebuffer::translated].
+pub struct FramebufferView<'a, 'b, C> {
+    fb: &'a mut Framebuffer<'b, C>,
+    // Position of the view origin in the framebuffer.
+    offset: Point,
+    // Visible area, in framebuffer coordinates.
+    clip: Rectangle,
+}
+
+impl<'b, C> FramebufferView<'_, 'b, C> {
+    /// Returns a view of this view with the origin moved by `offset`, and
+    /// the same clipping.
+    pub fn translated(&mut self, offset: Point) -> FramebufferView<'_, 'b, C> {
+        FramebufferView {
+            fb: self.fb,
+            offset: self.offset + offset,
+            clip: self.clip,
+        }
+    }
+}
+
+impl<C> Dimensions for FramebufferView<'_, '_, C> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(self.clip.top_left - self.offset, self.clip.size)
+    }
+}
+
+impl<C> DrawTarget for FramebufferView<'_, '_, C>
+where
+    C: PixelColor + ToBytes,
+    C::Bytes: AsRef<[u8]>,
+{
+    type Color = C;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let (offset, clip) = (self.offset, self.clip);
+        self.fb.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(p, c)| Pixel(p + offset, c))
+                .filter(|Pixel(p, _)| clip.contains(*p)),
+        )
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let target = Rectangle::new(area.top_left + self.offset, area.size);
+        if target.intersection(&self.clip) == target {
+            self.fb.fill_contiguous(&target, colors)
+        } else {
+            // The framebuffer would only clip to its own bounds.
+            self.draw_iter(area.points().zip(colors).map(|(p, c)| Pixel(p, c)))
+        }
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let target = Rectangle::new(area.top_left + self.offset, area.size);
+        self.fb.fill_solid(&target.intersection(&self.clip), color)
+    }
+}